@@ -1,26 +1,19 @@
-//! # Run-to-Completion (RTC) — External Event Loop
+//! # Run-to-Completion (RTC) — Posted Events
 //!
 //! Transition actions in phyto-fsm are atomic: the FSM is mutably borrowed for
 //! the entire transition, so an action cannot call back into the FSM to trigger
 //! another event. Rust's borrow checker enforces this at compile time.
 //!
-//! When an action needs to trigger a follow-up event, one way is to use a channel to signal
-//! the intent and process it in an external event loop after the transition completes.
-
-use std::sync::mpsc;
+//! With `posted_capacity`, actions can post follow-up events instead. They are
+//! processed by the FSM after the current transition completes, before the
+//! event method returns.
 
 use phyto_fsm::generate_fsm;
-generate_fsm!(file_path = "test/actions/actions.puml");
-
-use test_fsm::{ITestFsmActions, ITestFsmEventParams};
+generate_fsm!(file_path = "test/actions/actions.puml", posted_capacity = 4);
 
-enum FollowUpEvent {
-    GoToA(i32),
-}
+use test_fsm::{ITestFsmActions, ITestFsmEventParams, TestFsmEvent, TestFsmPostedEvents};
 
-struct MyActions {
-    follow_up_tx: mpsc::Sender<FollowUpEvent>,
-}
+struct MyActions;
 
 impl ITestFsmEventParams for MyActions {
     type GoToBParams = ();
@@ -28,31 +21,25 @@ impl ITestFsmEventParams for MyActions {
 }
 
 impl ITestFsmActions for MyActions {
-    fn action1(&mut self, _event: ()) {
-        println!("Action1: transitioning to StateB, requesting follow-up GoToA");
-        self.follow_up_tx.send(FollowUpEvent::GoToA(42)).unwrap();
+    fn action1(&mut self, _event: (), posted: &mut TestFsmPostedEvents<Self>) {
+        println!("Action1: transitioning to StateB, posting follow-up GoToA");
+        if posted.post(TestFsmEvent::GoToA(42)).is_err() {
+            println!("Action1: too many events posted, dropping GoToA");
+        }
     }
 
-    fn action2(&mut self, event: i32) {
+    fn action2(&mut self, event: i32, _posted: &mut TestFsmPostedEvents<Self>) {
         println!("Action2: transitioning back to StateA with param={event}");
     }
 }
 
-// Action1 (fired during GoToB) signals a GoToA follow-up event through a
-// channel. The external loop picks it up and feeds it back into the FSM,
-// which then transitions StateB -> StateA and calls Action2.
+// Action1 (fired during GoToB) posts a GoToA follow-up event. Once the
+// transition to StateB completes, the FSM processes it, transitions
+// StateB -> StateA and calls Action2.
 fn main() {
-    let (tx, rx) = mpsc::channel();
-    let actions = MyActions { follow_up_tx: tx };
-    let mut fsm = test_fsm::start(actions);
-
-    // Trigger GoToB — Action1 fires and enqueues a follow-up GoToA
-    fsm.go_to_b(());
+    let mut fsm = test_fsm::start(MyActions);
 
-    // External event loop: drain follow-up events
-    while let Ok(event) = rx.try_recv() {
-        match event {
-            FollowUpEvent::GoToA(param) => fsm.go_to_a(param),
-        }
-    }
+    // Trigger GoToB — Action1 fires and posts a follow-up GoToA
+    let _ = fsm.go_to_b(());
+    println!("Back in {}", fsm.current_state());
 }
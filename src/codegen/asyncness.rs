@@ -0,0 +1,66 @@
+pub struct AsyncActionsCodegen {
+    /// Precedes the `fn` of the actions, the dispatching methods and the public event methods
+    pub fn_keyword: proc_macro2::TokenStream,
+    /// Appended to calls of actions, guards and dispatching methods
+    pub await_op: proc_macro2::TokenStream,
+    pub trait_attribute: proc_macro2::TokenStream,
+    enabled: bool,
+}
+
+impl AsyncActionsCodegen {
+    pub fn new(async_actions: bool) -> Self {
+        if async_actions {
+            Self::enabled()
+        } else {
+            Self::disabled()
+        }
+    }
+
+    fn disabled() -> Self {
+        Self {
+            fn_keyword: quote::quote! {},
+            await_op: quote::quote! {},
+            trait_attribute: quote::quote! {},
+            enabled: false,
+        }
+    }
+
+    fn enabled() -> Self {
+        Self {
+            fn_keyword: quote::quote! { async },
+            await_op: quote::quote! { .await },
+            // The futures of the actions are not required to be `Send`
+            trait_attribute: quote::quote! { #[allow(async_fn_in_trait)] },
+            enabled: true,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// The futures of the states are allocated on the heap
+    pub fn needs_alloc(&self) -> bool {
+        self.enabled
+    }
+
+    /// The return type of a state's function pointer, which borrows its arguments for `'a`
+    pub fn future_type(&self, output: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        if self.enabled {
+            quote::quote! {
+                ::core::pin::Pin<alloc::boxed::Box<dyn ::core::future::Future<Output = #output> + 'a>>
+            }
+        } else {
+            output
+        }
+    }
+
+    /// The body of a state's function pointer, evaluated to its `future_type`
+    pub fn future(&self, body: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        if self.enabled {
+            quote::quote! { alloc::boxed::Box::pin(async move { #body }) }
+        } else {
+            quote::quote! { { #body } }
+        }
+    }
+}
@@ -1,8 +1,16 @@
 use crate::fsm;
 
+use super::asyncness::AsyncActionsCodegen;
+use super::fallible::FallibleActionsCodegen;
 use super::ident;
+use super::logging::LoggingCodegen;
+use super::tracing::TracingCodegen;
 
 pub struct DeferredEventsCodegen {
+    /// The queue of the deferred events, a module level item
+    pub queue: proc_macro2::TokenStream,
+    /// Whether the queue is allocated on the heap
+    pub needs_alloc: bool,
     pub state_field: proc_macro2::TokenStream,
     pub state_clone_field: proc_macro2::TokenStream,
     pub state_init_field: proc_macro2::TokenStream,
@@ -10,68 +18,176 @@ pub struct DeferredEventsCodegen {
     pub fsm_init_field: proc_macro2::TokenStream,
     pub entry_method: proc_macro2::Ident,
     pub entry_point: proc_macro2::TokenStream,
+    /// Discards the pending deferred events of the FSM, reporting them as unhandled
+    pub discard_pending: proc_macro2::TokenStream,
     event_enum: Option<proc_macro2::Ident>,
+    capacity: Option<usize>,
 }
 
 impl DeferredEventsCodegen {
-    pub fn new(fsm: &fsm::UmlFsm, idents: &ident::Idents) -> Self {
+    pub fn new(
+        fsm: &fsm::UmlFsm,
+        idents: &ident::Idents,
+        fallible: &FallibleActionsCodegen,
+        asyncness: &AsyncActionsCodegen,
+        tracing: &TracingCodegen,
+        logging: &LoggingCodegen,
+        capacity: Option<usize>,
+    ) -> Self {
         let has_deferred = fsm.states().any(|s| s.deferred_events().next().is_some());
         if has_deferred {
-            Self::enabled(idents)
+            Self::enabled(idents, fallible, asyncness, tracing, logging, capacity)
         } else {
-            Self::disabled(idents)
+            Self::disabled(idents, fallible, asyncness)
         }
     }
 
-    fn disabled(idents: &ident::Idents) -> Self {
+    fn disabled(
+        idents: &ident::Idents,
+        fallible: &FallibleActionsCodegen,
+        asyncness: &AsyncActionsCodegen,
+    ) -> Self {
         let event_enum = &idents.event_enum;
+        let outcome_enum = &idents.outcome_enum;
+        let error_type = &fallible.error_type;
+        let fn_keyword = &asyncness.fn_keyword;
+        let await_op = &asyncness.await_op;
         Self {
+            queue: quote::quote! {},
+            needs_alloc: false,
             state_field: quote::quote! {},
             state_clone_field: quote::quote! {},
             state_init_field: quote::quote! {},
             fsm_field: quote::quote! {},
             fsm_init_field: quote::quote! {},
+            discard_pending: quote::quote! {},
             entry_method: quote::format_ident!("trigger_event"),
             entry_point: quote::quote! {
-                fn trigger_event(&mut self, event: #event_enum<A>) {
-                    self.try_event_based_transition(event);
-                    self.try_direct_transition();
+                #fn_keyword fn trigger_event(&mut self, event: #event_enum<A>) -> Result<#outcome_enum, #error_type> {
+                    let from = self.current_state.id;
+                    self.observer.on_event(from, event.kind());
+                    let outcome = self.try_event_based_transition(event)#await_op?;
+                    self.try_direct_transition()#await_op?;
+                    Ok(self.followed_by_direct_transitions(outcome, from))
                 }
             },
             event_enum: None,
+            capacity: None,
         }
     }
 
-    fn enabled(idents: &ident::Idents) -> Self {
+    fn enabled(
+        idents: &ident::Idents,
+        fallible: &FallibleActionsCodegen,
+        asyncness: &AsyncActionsCodegen,
+        tracing: &TracingCodegen,
+        logging: &LoggingCodegen,
+        capacity: Option<usize>,
+    ) -> Self {
+        let queue = &idents.deferred_queue;
         let event_enum = &idents.event_enum;
+        let outcome_enum = &idents.outcome_enum;
+        let error_type = &fallible.error_type;
+        let fn_keyword = &asyncness.fn_keyword;
+        let await_op = &asyncness.await_op;
+        let process_pending_event = tracing.instrument(
+            "deferred",
+            quote::quote! { self.process_event(event)#await_op },
+        );
+        let trace_defer = tracing.event(
+            quote::quote! { state = %self.current_state.id, event = %event_kind },
+            "defer",
+        );
+        let log_defer = logging.log(
+            "{} defers {}",
+            quote::quote! { self.current_state.id, event_kind },
+        );
+        let log_discard = logging.log(
+            "{} discards {}, the deferred events are full",
+            quote::quote! { self.current_state.id, event_kind },
+        );
+        let log_pending = logging.log(
+            "{} processes deferred {}",
+            quote::quote! { self.current_state.id, event.kind() },
+        );
         Self {
+            queue: match capacity {
+                Some(capacity) => ring_buffer_queue(queue, capacity),
+                None => heap_queue(queue),
+            },
+            needs_alloc: capacity.is_none(),
             state_field: quote::quote! { defer_event: fn(event: &#event_enum<A>) -> bool, },
             state_clone_field: quote::quote! { defer_event: self.defer_event, },
             state_init_field: quote::quote! { defer_event: |_event| false, },
-            fsm_field: quote::quote! { deferred_events: std::collections::VecDeque<#event_enum<A>>, },
-            fsm_init_field: quote::quote! { deferred_events: std::collections::VecDeque::new(), },
+            fsm_field: quote::quote! { deferred_events: #queue<#event_enum<A>>, },
+            fsm_init_field: quote::quote! { deferred_events: #queue::new(), },
+            discard_pending: quote::quote! {
+                while let Some(event) = self.deferred_events.pop_front() {
+                    self.actions.on_unhandled(self.current_state.id, event.kind());
+                }
+            },
             entry_method: quote::format_ident!("run_event_loop"),
             entry_point: quote::quote! {
-                fn run_event_loop(&mut self, event: #event_enum<A>) {
-                    let pending = std::mem::take(&mut self.deferred_events);
-                    std::iter::once(event)
-                        .chain(pending.into_iter())
-                        .for_each(|event| {
-                            self.process_event(event);
-                        });
+                #fn_keyword fn run_event_loop(&mut self, event: #event_enum<A>) -> Result<#outcome_enum, #error_type> {
+                    let outcome = self.process_event(event)#await_op?;
+                    if matches!(outcome, #outcome_enum::Transitioned { .. }) {
+                        let mut pending = ::core::mem::replace(&mut self.deferred_events, #queue::new());
+                        let result = self.process_pending(&mut pending)#await_op;
+                        if result.is_err() {
+                            // The events not processed yet stay pending, ahead of the newly deferred ones
+                            while let Some(event) = self.deferred_events.pop_front() {
+                                if let Err(event) = pending.push_back(event) {
+                                    self.actions.on_unhandled(self.current_state.id, event.kind());
+                                }
+                            }
+                            self.deferred_events = pending;
+                        }
+                        result?;
+                    }
+                    Ok(outcome)
+                }
+
+                #fn_keyword fn process_pending(
+                    &mut self,
+                    pending: &mut #queue<#event_enum<A>>,
+                ) -> Result<(), #error_type> {
+                    while let Some(event) = pending.pop_front() {
+                        #log_pending
+                        let result = { #process_pending_event };
+                        // Only the outcome of the triggered event is reported
+                        let _ = result?;
+                    }
+                    Ok(())
                 }
 
-                fn process_event(&mut self, event: #event_enum<A>) {
+                #fn_keyword fn process_event(&mut self, event: #event_enum<A>) -> Result<#outcome_enum, #error_type> {
+                    let event_kind = event.kind();
+                    self.observer.on_event(self.current_state.id, event_kind);
                     if (self.current_state.defer_event)(&event) {
-                        self.deferred_events.push_back(event);
-                        return;
+                        return match self.deferred_events.push_back(event) {
+                            Ok(()) => {
+                                self.observer.on_defer(self.current_state.id, event_kind);
+                                #trace_defer
+                                #log_defer
+                                Ok(#outcome_enum::Deferred)
+                            }
+                            Err(_) => {
+                                #log_discard
+                                self.actions.on_unhandled(self.current_state.id, event_kind);
+                                Ok(#outcome_enum::Ignored)
+                            }
+                        };
                     }
-                    if self.try_event_based_transition(event) {
-                        self.try_direct_transition();
+                    let from = self.current_state.id;
+                    let outcome = self.try_event_based_transition(event)#await_op?;
+                    if matches!(outcome, #outcome_enum::Transitioned { .. }) {
+                        self.try_direct_transition()#await_op?;
                     }
+                    Ok(self.followed_by_direct_transitions(outcome, from))
                 }
             },
             event_enum: Some(event_enum.clone()),
+            capacity,
         }
     }
 
@@ -99,4 +215,91 @@ impl DeferredEventsCodegen {
 
         quote::quote! { defer_event: #defer_fn, }
     }
+
+    /// Whether any state defers events
+    pub fn is_enabled(&self) -> bool {
+        self.event_enum.is_some()
+    }
+
+    /// Iterates the queue from the oldest event, only generated where it is needed
+    pub fn queue_iter(&self, idents: &ident::Idents) -> proc_macro2::TokenStream {
+        if !self.is_enabled() {
+            return quote::quote! {};
+        }
+        let queue = &idents.deferred_queue;
+        let iter = match self.capacity {
+            Some(capacity) => quote::quote! {
+                (0..self.len).filter_map(move |i| self.events[(self.head + i) % #capacity].as_ref())
+            },
+            None => quote::quote! { self.0.iter() },
+        };
+        quote::quote! {
+            impl<E> #queue<E> {
+                fn iter(&self) -> impl Iterator<Item = &E> {
+                    #iter
+                }
+            }
+        }
+    }
+}
+
+fn heap_queue(queue: &proc_macro2::Ident) -> proc_macro2::TokenStream {
+    quote::quote! {
+        /// The deferred events in the order of their deferral
+        struct #queue<E>(alloc::collections::VecDeque<E>);
+
+        impl<E> #queue<E> {
+            fn new() -> Self {
+                Self(alloc::collections::VecDeque::new())
+            }
+
+            /// Never fails, the queue grows as needed
+            fn push_back(&mut self, event: E) -> Result<(), E> {
+                self.0.push_back(event);
+                Ok(())
+            }
+
+            fn pop_front(&mut self) -> Option<E> {
+                self.0.pop_front()
+            }
+        }
+    }
+}
+
+fn ring_buffer_queue(queue: &proc_macro2::Ident, capacity: usize) -> proc_macro2::TokenStream {
+    quote::quote! {
+        /// The deferred events in the order of their deferral, stored inline
+        struct #queue<E> {
+            events: [Option<E>; #capacity],
+            head: usize,
+            len: usize,
+        }
+
+        impl<E> #queue<E> {
+            fn new() -> Self {
+                Self {
+                    events: [const { None }; #capacity],
+                    head: 0,
+                    len: 0,
+                }
+            }
+
+            /// Returns the event if the queue is full
+            fn push_back(&mut self, event: E) -> Result<(), E> {
+                if self.len == #capacity {
+                    return Err(event);
+                }
+                self.events[(self.head + self.len) % #capacity] = Some(event);
+                self.len += 1;
+                Ok(())
+            }
+
+            fn pop_front(&mut self) -> Option<E> {
+                let event = self.events[self.head].take()?;
+                self.head = (self.head + 1) % #capacity;
+                self.len -= 1;
+                Some(event)
+            }
+        }
+    }
 }
@@ -0,0 +1,237 @@
+use std::collections::HashMap;
+
+use crate::error::{Error, Result};
+use crate::fsm;
+
+use super::asyncness::AsyncActionsCodegen;
+
+/// The event of the transitions taken after an action failed, e.g. `Running --> Fault : error`
+const ERROR_EVENT: &str = "error";
+
+pub struct FallibleActionsCodegen {
+    /// The error type of the internal results, which is `Infallible` unless actions are fallible
+    pub error_type: proc_macro2::TokenStream,
+    /// Appended to action calls to propagate their errors
+    pub try_op: proc_macro2::TokenStream,
+    pub trait_error_type: proc_macro2::TokenStream,
+    pub action_result: proc_macro2::TokenStream,
+    pub state_field: proc_macro2::TokenStream,
+    pub state_clone_field: proc_macro2::TokenStream,
+    pub state_init_field: proc_macro2::TokenStream,
+    pub recover_method: proc_macro2::TokenStream,
+    /// The function of the error state by the qualified name of the state leaving on an error
+    error_states: Option<HashMap<String, proc_macro2::Ident>>,
+}
+
+impl FallibleActionsCodegen {
+    /// With fallible actions, the error transitions are removed from the FSM, as `error` is not
+    /// an event which can be triggered.
+    pub fn new(
+        fsm: &mut fsm::UmlFsm,
+        fallible_actions: bool,
+        asyncness: &AsyncActionsCodegen,
+    ) -> Result<Self> {
+        if fallible_actions {
+            let error_states = extract_error_states(fsm)?;
+            fsm.retain_transitions(|t| !is_error_event(t.event.as_ref()));
+            Ok(Self::enabled(error_states, asyncness))
+        } else {
+            Ok(Self::disabled(asyncness))
+        }
+    }
+
+    fn disabled(asyncness: &AsyncActionsCodegen) -> Self {
+        let error_type = quote::quote! { ::core::convert::Infallible };
+        let fn_keyword = &asyncness.fn_keyword;
+        Self {
+            try_op: quote::quote! {},
+            trait_error_type: quote::quote! {},
+            action_result: quote::quote! {},
+            state_field: quote::quote! {},
+            state_clone_field: quote::quote! {},
+            state_init_field: quote::quote! {},
+            recover_method: quote::quote! {
+                #fn_keyword fn recover<T>(&mut self, result: Result<T, #error_type>) -> Result<T, #error_type> {
+                    result
+                }
+            },
+            error_type,
+            error_states: None,
+        }
+    }
+
+    fn enabled(
+        error_states: HashMap<String, proc_macro2::Ident>,
+        asyncness: &AsyncActionsCodegen,
+    ) -> Self {
+        let error_type = quote::quote! { A::Error };
+        let fn_keyword = &asyncness.fn_keyword;
+        let await_op = &asyncness.await_op;
+        Self {
+            try_op: quote::quote! { ? },
+            trait_error_type: quote::quote! {
+                /// The error of failing actions
+                type Error;
+            },
+            action_result: quote::quote! { -> Result<(), Self::Error> },
+            state_field: quote::quote! { error_state: fn() -> Option<Self>, },
+            state_clone_field: quote::quote! { error_state: self.error_state, },
+            state_init_field: quote::quote! { error_state: || None, },
+            recover_method: quote::quote! {
+                /// Enters the error state of the current state after an action failed, followed by
+                /// its direct transitions. Errors of the actions of these transitions are ignored.
+                #fn_keyword fn recover<T>(&mut self, result: Result<T, #error_type>) -> Result<T, #error_type> {
+                    if result.is_err() {
+                        if let Some(error_state) = (self.current_state.error_state)() {
+                            if self.change_state((error_state.enter_state)())#await_op.is_ok() {
+                                let _ = self.try_direct_transition()#await_op;
+                            }
+                        }
+                    }
+                    result
+                }
+            },
+            error_type,
+            error_states: Some(error_states),
+        }
+    }
+
+    pub fn state_field_value(&self, state: &fsm::State<'_>) -> proc_macro2::TokenStream {
+        let error_states = match &self.error_states {
+            Some(e) => e,
+            None => return quote::quote! {},
+        };
+
+        let error_state = if let Some(error_fn) = error_states.get(&state.qualified_name("::")) {
+            quote::quote! { || Some(Self::#error_fn()) }
+        } else if let Some(parent) = state.parent() {
+            let parent_fn = parent.function_ident();
+            quote::quote! { || (Self::#parent_fn().error_state)() }
+        } else {
+            quote::quote! { || None }
+        };
+
+        quote::quote! { error_state: #error_state, }
+    }
+
+    /// The return type of a public method, of which the internal result is `result_type`
+    pub fn public_type(&self, result_type: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        if self.error_states.is_some() {
+            quote::quote! { Result<#result_type, A::Error> }
+        } else {
+            result_type
+        }
+    }
+
+    /// Converts the internal result to the return value of a public method
+    pub fn public_value(&self, result: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        if self.error_states.is_some() {
+            result
+        } else {
+            quote::quote! {{
+                let Ok(value) = #result;
+                value
+            }}
+        }
+    }
+    /// The return type of `stop()`, which gives the actions back also when an exit action fails
+    pub fn stop_type(&self) -> proc_macro2::TokenStream {
+        if self.error_states.is_some() {
+            quote::quote! { Result<A, (A, A::Error)> }
+        } else {
+            quote::quote! { A }
+        }
+    }
+
+    /// Converts the internal result of exiting the active states to the return value of `stop()`
+    pub fn stop_value(&self, result: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        if self.error_states.is_some() {
+            quote::quote! {
+                match #result {
+                    Ok(()) => Ok(self.0.actions),
+                    Err(error) => Err((self.0.actions, error)),
+                }
+            }
+        } else {
+            quote::quote! {{
+                let Ok(()) = #result;
+                self.0.actions
+            }}
+        }
+    }
+}
+
+fn is_error_event(event: Option<&fsm::Event>) -> bool {
+    event.is_some_and(|e| e.0 == ERROR_EVENT)
+}
+
+fn extract_error_states(fsm: &fsm::UmlFsm) -> Result<HashMap<String, proc_macro2::Ident>> {
+    fsm.transitions()
+        .filter(|t| is_error_event(t.event))
+        .map(|t| {
+            let state_name = t.source.qualified_name("::");
+            let destination = t.destination.as_ref().ok_or_else(|| {
+                Error::Parse(format!(
+                    "The error transition of state '{state_name}' must have a target state"
+                ))
+            })?;
+            if t.action.is_some() || t.guard.is_some() {
+                return Err(Error::Parse(format!(
+                    "The error transition of state '{state_name}' cannot have an action or guard"
+                )));
+            }
+            Ok((state_name, destination.function_ident()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AsyncActionsCodegen, FallibleActionsCodegen};
+    use crate::fsm::{StateType, TransitionParameters, UmlFsm, UmlFsmBuilder};
+
+    fn fsm_with_error_transition(target: Option<&str>, action: Option<&str>) -> UmlFsm {
+        let mut builder = UmlFsmBuilder::new("Test");
+        builder.add_state("A", StateType::Enter);
+        builder.add_transition(TransitionParameters {
+            source: "A",
+            target: Some("B"),
+            event: Some("Go".into()),
+            action: None,
+            guard: None,
+        });
+        builder.add_transition(TransitionParameters {
+            source: "A",
+            target,
+            event: Some("error".into()),
+            action: action.map(Into::into),
+            guard: None,
+        });
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn error_transitions_are_removed() {
+        let mut fsm = fsm_with_error_transition(Some("Fault"), None);
+        FallibleActionsCodegen::new(&mut fsm, true, &AsyncActionsCodegen::new(false)).unwrap();
+        let events: Vec<_> = fsm.transitions().filter_map(|t| t.event.cloned()).collect();
+        assert_eq!(events, vec!["Go".into()]);
+
+        let mut fsm = fsm_with_error_transition(Some("Fault"), None);
+        FallibleActionsCodegen::new(&mut fsm, false, &AsyncActionsCodegen::new(false)).unwrap();
+        assert_eq!(fsm.transitions().count(), 2);
+    }
+
+    #[test]
+    fn invalid_error_transitions_are_rejected() {
+        let mut fsm = fsm_with_error_transition(None, None);
+        assert!(
+            FallibleActionsCodegen::new(&mut fsm, true, &AsyncActionsCodegen::new(false)).is_err()
+        );
+
+        let mut fsm = fsm_with_error_transition(Some("Fault"), Some("Log"));
+        assert!(
+            FallibleActionsCodegen::new(&mut fsm, true, &AsyncActionsCodegen::new(false)).is_err()
+        );
+    }
+}
@@ -1,3 +1,5 @@
+use itertools::Itertools;
+
 use super::{GenerationContext, extract};
 
 pub fn generate_event_params_trait(ctx: &GenerationContext) -> proc_macro2::TokenStream {
@@ -15,11 +17,14 @@ pub fn generate_event_params_trait(ctx: &GenerationContext) -> proc_macro2::Toke
 }
 
 pub fn generate_action_trait(ctx: &GenerationContext) -> proc_macro2::TokenStream {
+    let action_result = &ctx.fallible.action_result;
+    let fn_keyword = &ctx.asyncness.fn_keyword;
+    let posted = &ctx.posted.action_param;
     let action_methods = extract::actions(ctx.fsm).map(|(action, event)| {
         let action_ident = action.ident();
         let params_ident = event.params_ident();
         quote::quote! {
-            fn #action_ident(&mut self, params: Self::#params_ident);
+            #fn_keyword fn #action_ident(&mut self, params: Self::#params_ident, #posted) #action_result;
         }
     });
 
@@ -27,50 +32,108 @@ pub fn generate_action_trait(ctx: &GenerationContext) -> proc_macro2::TokenStrea
         let guard_ident = guard.ident();
         let params_ident = event.params_ident();
         quote::quote! {
-            fn #guard_ident(&self, event: &Self::#params_ident) -> bool;
+            #fn_keyword fn #guard_ident(&self, event: &Self::#params_ident) -> bool;
         }
     });
 
     let direct_action_methods = extract::direct_transition_actions(ctx.fsm).map(|action| {
         let action_ident = action.ident();
         quote::quote! {
-            fn #action_ident(&mut self);
+            #fn_keyword fn #action_ident(&mut self, #posted) #action_result;
         }
     });
 
     let direct_guard_methods = extract::direct_transition_guards(ctx.fsm).map(|guard| {
         let guard_ident = guard.ident();
         quote::quote! {
-            fn #guard_ident(&self) -> bool;
+            #fn_keyword fn #guard_ident(&self) -> bool;
         }
     });
 
     let enter_methods = extract::enter_actions(ctx.fsm).map(|action| {
         let action_ident = action.ident();
         quote::quote! {
-            fn #action_ident(&mut self);
+            #fn_keyword fn #action_ident(&mut self, #posted) #action_result;
         }
     });
 
     let exit_methods = extract::exit_actions(ctx.fsm).map(|action| {
         let action_ident = action.ident();
         quote::quote! {
-            fn #action_ident(&mut self);
+            #fn_keyword fn #action_ident(&mut self, #posted) #action_result;
         }
     });
 
     let event_params_trait = &ctx.idents.event_params_trait;
     let trait_ident = &ctx.idents.action_trait;
+    let state_id_enum = &ctx.idents.state_id_enum;
+    let event_kind_enum = &ctx.idents.event_kind_enum;
+    let error_type = &ctx.fallible.trait_error_type;
+    let trait_attribute = &ctx.asyncness.trait_attribute;
+    let sized_bound = &ctx.posted.sized_bound;
 
     quote::quote! {
-        pub trait #trait_ident : #event_params_trait{
+        #trait_attribute
+        pub trait #trait_ident : #event_params_trait #sized_bound {
+            #error_type
             #(#action_methods)*
             #(#direct_action_methods)*
             #(#enter_methods)*
             #(#exit_methods)*
             #(#guard_methods)*
             #(#direct_guard_methods)*
+
+            /// Called for an event which the current state neither handles nor defers, including
+            /// deferred events which are discarded
+            fn on_unhandled(&mut self, state: #state_id_enum, event: #event_kind_enum) {
+                let _ = (state, event);
+            }
+        }
+    }
+}
+
+pub fn generate_observer_trait(ctx: &GenerationContext) -> proc_macro2::TokenStream {
+    let trait_ident = &ctx.idents.observer_trait;
+    let state_id_enum = &ctx.idents.state_id_enum;
+    let event_kind_enum = &ctx.idents.event_kind_enum;
+
+    quote::quote! {
+        /// Observes the steps of the FSM, e.g. for metrics or tests. Guards and actions are named
+        /// as written in the diagram. `()` observes nothing.
+        pub trait #trait_ident {
+            /// The current state evaluates an event, also when a deferred event is processed again
+            fn on_event(&mut self, state: #state_id_enum, event: #event_kind_enum) {
+                let _ = (state, event);
+            }
+
+            fn on_guard(&mut self, guard: &'static str, result: bool) {
+                let _ = (guard, result);
+            }
+
+            fn on_exit(&mut self, state: #state_id_enum) {
+                let _ = state;
+            }
+
+            /// The action of a transition is run
+            fn on_effect(&mut self, action: &'static str) {
+                let _ = action;
+            }
+
+            fn on_enter(&mut self, state: #state_id_enum) {
+                let _ = state;
+            }
+
+            fn on_defer(&mut self, state: #state_id_enum, event: #event_kind_enum) {
+                let _ = (state, event);
+            }
+
+            /// A transition without event is taken, `to` is its target
+            fn on_direct_transition(&mut self, from: #state_id_enum, to: #state_id_enum) {
+                let _ = (from, to);
+            }
         }
+
+        impl #trait_ident for () {}
     }
 }
 
@@ -83,29 +146,123 @@ pub fn generate_event_enum(ctx: &GenerationContext) -> proc_macro2::TokenStream
 
     let event_enum_ident = &ctx.idents.event_enum;
     let action_ident = &ctx.idents.action_trait;
+    let attributes = &ctx.snapshot.event_attributes;
     quote::quote! {
-        enum #event_enum_ident<P: #action_ident> {
+        #attributes
+        pub enum #event_enum_ident<P: #action_ident> {
             #(#event_variants)*
         }
     }
 }
 
-pub fn generate_event_enum_display(ctx: &GenerationContext) -> proc_macro2::TokenStream {
+pub fn generate_event_kind_enum(ctx: &GenerationContext) -> proc_macro2::TokenStream {
     let event_enum_ident = &ctx.idents.event_enum;
-    let event_variants = extract::events(ctx.fsm).map(|event| {
-        let event_ident = event.ident();
+    let event_kind_ident = &ctx.idents.event_kind_enum;
+    let action_ident = &ctx.idents.action_trait;
+    let logger_format = &ctx.logging.event_kind_format;
+
+    let variants: Vec<_> = extract::events(ctx.fsm)
+        .map(|event| event.ident())
+        .collect();
+    let count = variants.len();
+    let kind_match_arms = variants.iter().map(|variant| {
+        quote::quote! { #event_enum_ident::#variant(_) => #event_kind_ident::#variant, }
+    });
+    let name_match_arms = extract::events(ctx.fsm).map(|event| {
+        let variant = event.ident();
         let event_name = &event.0;
-        quote::quote! { #event_enum_ident::#event_ident(_) => #event_name, }
+        quote::quote! { #event_kind_ident::#variant => #event_name, }
     });
 
+    quote::quote! {
+        /// The events without their parameters
+        #[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+        pub enum #event_kind_ident {
+            #(#variants,)*
+        }
+
+        #logger_format
+
+        impl #event_kind_ident {
+            pub const ALL: [Self; #count] = [#(Self::#variants),*];
+
+            /// The event name as written in the diagram
+            pub fn name(self) -> &'static str {
+                match self {
+                    #(#name_match_arms)*
+                }
+            }
+        }
+
+        impl<P: #action_ident> #event_enum_ident<P> {
+            pub fn kind(&self) -> #event_kind_ident {
+                match self {
+                    #(#kind_match_arms)*
+                }
+            }
+        }
+    }
+}
+
+pub fn generate_event_enum_display(ctx: &GenerationContext) -> proc_macro2::TokenStream {
+    let event_enum_ident = &ctx.idents.event_enum;
+    let event_kind_ident = &ctx.idents.event_kind_enum;
     let action_ident = &ctx.idents.action_trait;
+
+    let debug_bounds = extract::events(ctx.fsm).map(|event| {
+        let params_ident = event.params_ident();
+        quote::quote! { P::#params_ident: ::core::fmt::Debug, }
+    });
+    let clone_bounds = extract::events(ctx.fsm).map(|event| {
+        let params_ident = event.params_ident();
+        quote::quote! { P::#params_ident: Clone, }
+    });
+    let clone_match_arms = extract::events(ctx.fsm).map(|event| {
+        let event_ident = event.ident();
+        quote::quote! {
+            #event_enum_ident::#event_ident(params) => #event_enum_ident::#event_ident(params.clone()),
+        }
+    });
+    let debug_match_arms = extract::events(ctx.fsm).map(|event| {
+        let event_ident = event.ident();
+        let event_name = event_ident.to_string();
+        quote::quote! {
+            #event_enum_ident::#event_ident(params) => f.debug_tuple(#event_name).field(params).finish(),
+        }
+    });
+
     quote::quote! {
-        impl<P: #action_ident> std::fmt::Display for #event_enum_ident<P> {
-            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-                let name = match self {
-                    #(#event_variants)*
-                };
-                write!(f, "{}", name)
+        impl ::core::fmt::Display for #event_kind_ident {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                write!(f, "{}", self.name())
+            }
+        }
+
+        impl<P: #action_ident> ::core::fmt::Display for #event_enum_ident<P> {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                write!(f, "{}", self.kind())
+            }
+        }
+
+        impl<P: #action_ident> Clone for #event_enum_ident<P>
+        where
+            #(#clone_bounds)*
+        {
+            fn clone(&self) -> Self {
+                match self {
+                    #(#clone_match_arms)*
+                }
+            }
+        }
+
+        impl<P: #action_ident> ::core::fmt::Debug for #event_enum_ident<P>
+        where
+            #(#debug_bounds)*
+        {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                match self {
+                    #(#debug_match_arms)*
+                }
             }
         }
     }
@@ -114,6 +271,8 @@ pub fn generate_event_enum_display(ctx: &GenerationContext) -> proc_macro2::Toke
 pub fn generate_state_id_enum(ctx: &GenerationContext) -> proc_macro2::TokenStream {
     let state_id_enum = &ctx.idents.state_id_enum;
     let init_state_id_variant = &ctx.idents.init_state_id_variant;
+    let serde_derive = &ctx.snapshot.state_id_derive;
+    let logger_format = &ctx.logging.state_id_format;
 
     let variants = ctx.fsm.states().map(|state| {
         let variant_ident = state.state_id_variant_ident();
@@ -126,13 +285,33 @@ pub fn generate_state_id_enum(ctx: &GenerationContext) -> proc_macro2::TokenStre
         quote::quote! { #state_id_enum::#variant_ident => #name_literal, }
     });
 
+    let parent_match_arms = ctx.fsm.states().filter_map(|state| {
+        let variant_ident = state.state_id_variant_ident();
+        let parent_variant_ident = state.parent()?.state_id_variant_ident();
+        Some(quote::quote! { #state_id_enum::#variant_ident => Some(#state_id_enum::#parent_variant_ident), })
+    });
+
     quote::quote! {
-        #[derive(Copy, Clone, PartialEq, Eq, Debug)]
-        enum #state_id_enum {
+        #[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+        #serde_derive
+        pub enum #state_id_enum {
             #(#variants)*
+            #[doc(hidden)]
             #init_state_id_variant,
         }
 
+        #logger_format
+
+        impl #state_id_enum {
+            /// The composite state containing this state
+            pub fn parent(self) -> Option<Self> {
+                match self {
+                    #(#parent_match_arms)*
+                    _ => None,
+                }
+            }
+        }
+
         impl From<#state_id_enum> for &'static str {
             fn from(id: #state_id_enum) -> Self {
                 match id {
@@ -142,8 +321,8 @@ pub fn generate_state_id_enum(ctx: &GenerationContext) -> proc_macro2::TokenStre
             }
         }
 
-        impl std::fmt::Display for #state_id_enum {
-            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        impl ::core::fmt::Display for #state_id_enum {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
                 let name: &'static str = (*self).into();
                 write!(f, "{}", name)
             }
@@ -151,28 +330,78 @@ pub fn generate_state_id_enum(ctx: &GenerationContext) -> proc_macro2::TokenStre
     }
 }
 
+pub fn generate_outcome_enum(ctx: &GenerationContext) -> proc_macro2::TokenStream {
+    let outcome_enum = &ctx.idents.outcome_enum;
+    let transition_enum = &ctx.idents.transition_enum;
+    let state_id_enum = &ctx.idents.state_id_enum;
+
+    quote::quote! {
+        /// How an event was dispatched, including the direct transitions following it
+        #[derive(Copy, Clone, PartialEq, Eq, Debug)]
+        #[must_use]
+        pub enum #outcome_enum {
+            /// The current state changed, or a self-transition was taken
+            Transitioned { from: #state_id_enum, to: #state_id_enum },
+            /// An internal transition was taken, the state was not left
+            Internal,
+            /// The event was deferred by the current state
+            Deferred,
+            /// No transition matched the event
+            Ignored,
+            /// Transitions matched the event, but all their guards were false
+            GuardRejected,
+        }
+
+        /// The result of a state's transition function
+        enum #transition_enum<S> {
+            To(S),
+            Internal,
+            GuardRejected,
+            Ignored,
+        }
+    }
+}
+
 pub fn generate_state_struct(ctx: &GenerationContext) -> proc_macro2::TokenStream {
     let state_ident = &ctx.idents.state_struct;
     let state_id_enum = &ctx.idents.state_id_enum;
     let actions_trait = &ctx.idents.action_trait;
+    let observer_trait = &ctx.idents.observer_trait;
     let event_enum = &ctx.idents.event_enum;
+    let transition_enum = &ctx.idents.transition_enum;
+
+    let error_type = &ctx.fallible.error_type;
 
     let defer_field = &ctx.deferred.state_field;
     let defer_clone = &ctx.deferred.state_clone_field;
+    let error_state_field = &ctx.fallible.state_field;
+    let error_state_clone = &ctx.fallible.state_clone_field;
+
+    let transition_result = ctx
+        .asyncness
+        .future_type(quote::quote! { Result<#transition_enum<Self>, #error_type> });
+    let direct_transition_result = ctx
+        .asyncness
+        .future_type(quote::quote! { Result<Option<Self>, #error_type> });
+    let action_result = ctx
+        .asyncness
+        .future_type(quote::quote! { Result<(), #error_type> });
+    let posted = &ctx.posted.state_param_type;
 
     quote::quote! {
         #[derive(Copy)]
-        struct #state_ident<A: #actions_trait> {
+        struct #state_ident<A: #actions_trait, O: #observer_trait> {
             id: #state_id_enum,
-            transition: fn(event: #event_enum<A>, actions: &mut A) -> Option<Self>,
-            direct_transition: fn(actions: &mut A) -> Option<Self>,
+            transition: for<'a> fn(event: #event_enum<A>, actions: &'a mut A, observer: &'a mut O, #posted) -> #transition_result,
+            direct_transition: for<'a> fn(actions: &'a mut A, observer: &'a mut O, #posted) -> #direct_transition_result,
             enter_state: fn() -> Self,
-            enter: fn(&mut A, from: &Self),
-            exit: fn(&mut A, to: &Self),
+            enter: for<'a> fn(&'a mut A, &'a mut O, #posted from: #state_id_enum) -> #action_result,
+            exit: for<'a> fn(&'a mut A, &'a mut O, #posted to: #state_id_enum) -> #action_result,
             #defer_field
+            #error_state_field
         }
 
-        impl<A: #actions_trait> Clone for #state_ident<A> {
+        impl<A: #actions_trait, O: #observer_trait> Clone for #state_ident<A, O> {
             fn clone(&self) -> Self {
                 Self {
                     id: self.id,
@@ -182,11 +411,12 @@ pub fn generate_state_struct(ctx: &GenerationContext) -> proc_macro2::TokenStrea
                     enter: self.enter,
                     exit: self.exit,
                     #defer_clone
+                    #error_state_clone
                 }
             }
         }
 
-        impl<A: #actions_trait> PartialEq for #state_ident<A> {
+        impl<A: #actions_trait, O: #observer_trait> PartialEq for #state_ident<A, O> {
             fn eq(&self, other: &Self) -> bool {
                 self.id == other.id
             }
@@ -199,31 +429,42 @@ pub fn generate_state_impl(ctx: &GenerationContext) -> proc_macro2::TokenStream
     let init_state_id_variant = &ctx.idents.init_state_id_variant;
     let fsm_enter_fn = ctx.fsm.enter_state().function_ident();
 
+    let transition_enum = &ctx.idents.transition_enum;
+    let event_enum = &ctx.idents.event_enum;
+    let try_op = &ctx.fallible.try_op;
+    let await_op = &ctx.asyncness.await_op;
+    let posted = &ctx.posted.state_param;
+    let posted_arg = &ctx.posted.action_arg;
+
     let state_fns = ctx.fsm.states().map(|state| {
         let state_id_variant = state.state_id_variant_ident();
         let fn_name = state.function_ident();
 
         let transitions = state.transitions().filter_map(|t| {
             let event_ident = t.event?.ident();
-            let event_enum = &ctx.idents.event_enum;
             let next_state = t
                 .destination
                 .as_ref()
                 .map(|d| {
                     let fn_ident = d.function_ident();
-                    quote::quote! { Some(Self::#fn_ident()) }
+                    quote::quote! { #transition_enum::To(Self::#fn_ident()) }
                 })
-                .unwrap_or_else(|| quote::quote! { None });
+                .unwrap_or_else(|| quote::quote! { #transition_enum::Internal });
             let action = if let Some(a) = t.action {
                 let action_ident = a.ident();
-                quote::quote! { action.#action_ident(params); }
+                let effect = observed_effect(ctx, a);
+                quote::quote! {
+                    #effect
+                    action.#action_ident(params, #posted_arg)#await_op #try_op;
+                }
             } else {
                 quote::quote! {}
             };
 
             let guard_condition = if let Some(g) = t.guard {
                 let guard_ident = g.ident();
-                quote::quote! { if action.#guard_ident(&params) }
+                let guard = observed_guard(ctx, g, quote::quote! { action.#guard_ident(&params)#await_op });
+                quote::quote! { if #guard }
             } else {
                 quote::quote! {}
             };
@@ -231,7 +472,7 @@ pub fn generate_state_impl(ctx: &GenerationContext) -> proc_macro2::TokenStream
             Some(quote::quote! {
                 #event_enum::#event_ident(params) #guard_condition => {
                     #action
-                    #next_state
+                    Ok(#next_state)
                 }
             })
         });
@@ -241,35 +482,65 @@ pub fn generate_state_impl(ctx: &GenerationContext) -> proc_macro2::TokenStream
             quote::quote! {
                     {
                     let parent = Self::#parent_fn();
-                    (parent.transition)(event, action)
+                    (parent.transition)(event, action, observer, #posted)#await_op
                 }
             }
         } else {
             quote::quote! {
-                None
+                Ok(#transition_enum::Ignored)
+            }
+        };
+
+        let guarded_events: Vec<_> = state
+            .transitions()
+            .filter(|t| t.guard.is_some())
+            .filter_map(|t| t.event)
+            .unique()
+            .map(|event| {
+                let event_ident = event.ident();
+                quote::quote! { #event_enum::#event_ident(_) }
+            })
+            .collect();
+        let unhandled = if guarded_events.is_empty() {
+            parent_transition
+        } else {
+            quote::quote! {
+                {
+                    let guard_rejected = matches!(event, #(#guarded_events)|*);
+                    let transition = #parent_transition;
+                    transition.map(|transition| match transition {
+                        #transition_enum::Ignored if guard_rejected => #transition_enum::GuardRejected,
+                        transition => transition,
+                    })
+                }
             }
         };
 
         let enter_state = state.enter_state();
         let enter_fn = enter_state.function_ident();
-        let enter_action = generate_enter_action(&state, state_id_enum);
-        let exit_action = generate_exit_action(&state, state_id_enum);
-        let direct_transition = generate_direct_transition(&state);
+        let enter_action = generate_enter_action(ctx, &state);
+        let exit_action = generate_exit_action(ctx, &state);
+        let direct_transition = generate_direct_transition(ctx, &state);
+        let transition = ctx.asyncness.future(quote::quote! {
+            match event {
+                #(#transitions,)*
+                _ => #unhandled,
+            }
+        });
         let defer_event = ctx.deferred.state_field_value(&state);
+        let error_state = ctx.fallible.state_field_value(&state);
 
         quote::quote! {
             fn #fn_name() -> Self {
                 Self {
                     id: #state_id_enum::#state_id_variant,
-                    transition: |event, action| match event {
-                        #(#transitions,)*
-                        _ => #parent_transition,
-                    },
+                    transition: |event, action, observer, #posted| #transition,
                     direct_transition: #direct_transition,
                     enter_state: Self::#enter_fn,
                     enter: #enter_action,
                     exit: #exit_action,
                     #defer_event
+                    #error_state
                 }
             }
         }
@@ -277,22 +548,36 @@ pub fn generate_state_impl(ctx: &GenerationContext) -> proc_macro2::TokenStream
 
     let struct_ident = &ctx.idents.state_struct;
     let actions_trait = &ctx.idents.action_trait;
+    let observer_trait = &ctx.idents.observer_trait;
     let init_defer = &ctx.deferred.state_init_field;
+    let init_error_state = &ctx.fallible.state_init_field;
+    let ignored = ctx
+        .asyncness
+        .future(quote::quote! { Ok(#transition_enum::Ignored) });
+    let enter_fsm = ctx
+        .asyncness
+        .future(quote::quote! { Ok(Some(Self::#fsm_enter_fn())) });
+    let no_action = ctx.asyncness.future(quote::quote! { Ok(()) });
+    let snapshot_functions = &ctx.snapshot.state_functions;
+    let unused_posted = &ctx.posted.unused_state_param;
     quote::quote! {
-        impl<A: #actions_trait> #struct_ident<A> {
+        impl<A: #actions_trait, O: #observer_trait> #struct_ident<A, O> {
             fn init() -> Self {
                 Self {
                     id: #state_id_enum::#init_state_id_variant,
-                    transition: |_event, _action| None,
-                    direct_transition: |_action| Some(Self::#fsm_enter_fn()),
+                    transition: |_event, _action, _observer, #unused_posted| #ignored,
+                    direct_transition: |_action, _observer, #unused_posted| #enter_fsm,
                     enter_state: Self::init,
-                    enter: |_actions, _from| {},
-                    exit: |_actions, _to| {},
+                    enter: |_actions, _observer, #unused_posted _from| #no_action,
+                    exit: |_actions, _observer, #unused_posted _to| #no_action,
                     #init_defer
+                    #init_error_state
                 }
             }
 
             #(#state_fns)*
+
+            #snapshot_functions
         }
     }
 }
@@ -301,75 +586,233 @@ pub fn generate_fsm(ctx: &GenerationContext) -> proc_macro2::TokenStream {
     let fsm = &ctx.idents.fsm;
     let fsm_inner = &ctx.idents.fsm_inner;
     let action = &ctx.idents.action_trait;
+    let observer = &ctx.idents.observer_trait;
     let state = &ctx.idents.state_struct;
     let event_enum = &ctx.idents.event_enum;
     let event_params_trait = &ctx.idents.event_params_trait;
+    let state_id_enum = &ctx.idents.state_id_enum;
+    let init_state_id_variant = &ctx.idents.init_state_id_variant;
+    let outcome_enum = &ctx.idents.outcome_enum;
+    let error_type = &ctx.fallible.error_type;
+    let recover_method = &ctx.fallible.recover_method;
+    let fn_keyword = &ctx.asyncness.fn_keyword;
+    let await_op = &ctx.asyncness.await_op;
 
     let deferred_field = &ctx.deferred.fsm_field;
     let deferred_init = &ctx.deferred.fsm_init_field;
+    let posted_field = &ctx.posted.fsm_field;
+    let posted_init = &ctx.posted.fsm_init_field;
+    let posted = &ctx.posted.fsm_arg;
+    let posted_methods = &ctx.posted.fsm_methods;
+    let discard_deferred = &ctx.deferred.discard_pending;
+    let dispatch_posted = ctx.posted.run_to_completion(quote::quote! { self });
+    let start_posted = ctx.posted.run_to_completion(quote::quote! { fsm });
+    let snapshot_methods = &ctx.snapshot.fsm_methods;
+    let log_direct_transition = ctx.logging.log(
+        "{} --> {}, entering {}",
+        quote::quote! { self.current_state.id, transition_state.id, enter_state.id },
+    );
+    let log_initial_transition = ctx.logging.log(
+        "[*] --> {}, entering {}",
+        quote::quote! { transition_state.id, enter_state.id },
+    );
 
     let fsm_struct = quote::quote! {
-        struct #fsm_inner<A: #action> {
+        struct #fsm_inner<A: #action, O: #observer> {
             actions: A,
-            current_state: #state<A>,
+            observer: O,
+            current_state: #state<A, O>,
             #deferred_field
+            #posted_field
         }
-        pub struct #fsm<A: #action>(#fsm_inner<A>);
+        pub struct #fsm<A: #action, O: #observer = ()>(#fsm_inner<A, O>);
     };
 
     let trigger_event = generate_trigger_event(ctx);
 
     let entry_method = &ctx.deferred.entry_method;
+    let dispatch_body = ctx.tracing.instrument(
+        "dispatch",
+        quote::quote! {
+            let result = self.#entry_method(event)#await_op;
+            self.recover(result)#await_op
+        },
+    );
 
+    let outcome_type = ctx.fallible.public_type(quote::quote! { #outcome_enum });
     let methods = extract::events(ctx.fsm).map(|event| {
         let fn_ident = event.method_ident();
         let event_ident = event.ident();
         let params_ident = event.params_ident();
+        let outcome = ctx
+            .fallible
+            .public_value(quote::quote! { self.0.dispatch(#event_enum::#event_ident(params))#await_op });
         quote::quote! {
-            pub fn #fn_ident(&mut self, params: <A as #event_params_trait>::#params_ident) {
-                self.0.#entry_method(#event_enum::#event_ident(params));
+            #[must_use = "the outcome tells whether the event was handled"]
+            pub #fn_keyword fn #fn_ident(&mut self, params: <A as #event_params_trait>::#params_ident) -> #outcome_type {
+                #outcome
             }
         }
     });
+    let trigger_outcome = ctx
+        .fallible
+        .public_value(quote::quote! { self.0.dispatch(event)#await_op });
+    let fsm_type = ctx.fallible.public_type(quote::quote! { #fsm<A> });
+    let observed_fsm_type = ctx.fallible.public_type(quote::quote! { #fsm<A, O> });
+    let stop_type = ctx.fallible.stop_type();
+    let stopped_actions = ctx
+        .fallible
+        .stop_value(quote::quote! { self.0.stop()#await_op });
+    let started_fsm = ctx
+        .fallible
+        .public_value(quote::quote! { #fsm_inner::start(actions, observer)#await_op.map(#fsm) });
 
     let common_impl = quote::quote! {
-        impl<A> #fsm_inner<A>
+        impl<A, O> #fsm_inner<A, O>
         where
             A: #action,
+            O: #observer,
         {
-            fn start(actions: A) -> Self {
+            #fn_keyword fn start(actions: A, observer: O) -> Result<Self, #error_type> {
                 let mut fsm = Self {
                     actions,
+                    observer,
                     current_state: #state::init(),
                     #deferred_init
+                    #posted_init
                 };
-                fsm.try_direct_transition();
-                fsm
+                let result = fsm.try_direct_transition()#await_op;
+                let result = fsm.recover(result)#await_op;
+                #start_posted
+                result.map(|()| fsm)
+            }
+
+            #fn_keyword fn dispatch(&mut self, event: #event_enum<A>) -> Result<#outcome_enum, #error_type> {
+                let result = { #dispatch_body };
+                #dispatch_posted
+                result
             }
 
-            fn change_state(&mut self, next_state: #state<A>) {
-                (self.current_state.exit)(&mut self.actions, &next_state);
-                (next_state.enter)(&mut self.actions, &self.current_state);
-                self.current_state = next_state;
+            /// Exits the current state and enters the next one. If an exit action fails, the
+            /// current state is kept. If an enter action fails, the next state is current.
+            #fn_keyword fn change_state(&mut self, next_state: #state<A, O>) -> Result<(), #error_type> {
+                (self.current_state.exit)(&mut self.actions, &mut self.observer, #posted next_state.id)#await_op?;
+                let from = ::core::mem::replace(&mut self.current_state, next_state);
+                (self.current_state.enter)(&mut self.actions, &mut self.observer, #posted from.id)#await_op
             }
 
-            fn try_direct_transition(&mut self) {
-                while let Some(transition_state) = (self.current_state.direct_transition)(&mut self.actions) {
+            #fn_keyword fn try_direct_transition(&mut self) -> Result<(), #error_type> {
+                while let Some(transition_state) =
+                    (self.current_state.direct_transition)(&mut self.actions, &mut self.observer, #posted)#await_op?
+                {
                     let enter_state = (transition_state.enter_state)();
-                    self.change_state(enter_state);
+                    // The initial transition on start is not reported, the initial state is hidden
+                    if self.current_state.id != #state_id_enum::#init_state_id_variant {
+                        self.observer.on_direct_transition(self.current_state.id, transition_state.id);
+                        #log_direct_transition
+                    } else {
+                        #log_initial_transition
+                    }
+                    self.change_state(enter_state)#await_op?;
+                }
+                Ok(())
+            }
+
+            /// Discards the deferred events still pending, they are reported as unhandled
+            fn discard_deferred(&mut self) {
+                #discard_deferred
+            }
+
+            /// Exits the active states, from the current state to its outermost parent
+            #fn_keyword fn stop(&mut self) -> Result<(), #error_type> {
+                self.discard_deferred();
+                let to = #state_id_enum::#init_state_id_variant;
+                (self.current_state.exit)(&mut self.actions, &mut self.observer, #posted to)#await_op
+            }
+
+            #recover_method
+
+            #posted_methods
+
+            /// Updates the outcome of an event by the direct transitions that followed it
+            fn followed_by_direct_transitions(&self, outcome: #outcome_enum, from: #state_id_enum) -> #outcome_enum {
+                let to = self.current_state.id;
+                match outcome {
+                    #outcome_enum::Transitioned { from, .. } => #outcome_enum::Transitioned { from, to },
+                    _ if from != to => #outcome_enum::Transitioned { from, to },
+                    outcome => outcome,
                 }
             }
         }
 
-        impl<A> #fsm<A>
+        impl<A, O> #fsm<A, O>
         where
             A: #action,
+            O: #observer,
         {
             #(#methods)*
+
+            /// Triggers the event, e.g. when events are received from a queue
+            #[must_use = "the outcome tells whether the event was handled"]
+            pub #fn_keyword fn trigger(&mut self, event: #event_enum<A>) -> #outcome_type {
+                #trigger_outcome
+            }
+
+            /// The innermost active state
+            pub fn current_state(&self) -> #state_id_enum {
+                self.0.current_state.id
+            }
+
+            /// The active states, from the current state to its outermost parent
+            pub fn active_states(&self) -> impl Iterator<Item = #state_id_enum> {
+                ::core::iter::successors(Some(self.current_state()), |id| id.parent())
+            }
+
+            /// Whether the state is active, i.e. it is the current state or one of its parents
+            pub fn is_in(&self, state: #state_id_enum) -> bool {
+                self.active_states().any(|id| id == state)
+            }
+
+            pub fn actions(&self) -> &A {
+                &self.0.actions
+            }
+
+            pub fn actions_mut(&mut self) -> &mut A {
+                &mut self.0.actions
+            }
+
+            pub fn observer(&self) -> &O {
+                &self.0.observer
+            }
+
+            pub fn observer_mut(&mut self) -> &mut O {
+                &mut self.0.observer
+            }
+
+            /// Gives the actions back without running any exit actions. Deferred events still
+            /// pending are discarded and reported to `on_unhandled`.
+            pub fn into_actions(mut self) -> A {
+                self.0.discard_deferred();
+                self.0.actions
+            }
+
+            /// Runs the exit actions of the active states, from the current state to its outermost
+            /// parent, and gives the actions back. Deferred events still pending are discarded and
+            /// reported to `on_unhandled` first.
+            pub #fn_keyword fn stop(mut self) -> #stop_type {
+                #stopped_actions
+            }
+
+            #snapshot_methods
         }
 
-        pub fn start<A: #action>(actions: A) -> #fsm<A> {
-            #fsm(#fsm_inner::start(actions))
+        pub #fn_keyword fn start<A: #action>(actions: A) -> #fsm_type {
+            start_with_observer(actions, ())#await_op
+        }
+
+        /// Starts the FSM, reporting its steps to the observer
+        pub #fn_keyword fn start_with_observer<A: #action, O: #observer>(actions: A, observer: O) -> #observed_fsm_type {
+            #started_fsm
         }
     };
 
@@ -383,71 +826,82 @@ pub fn generate_fsm(ctx: &GenerationContext) -> proc_macro2::TokenStream {
 fn generate_trigger_event(ctx: &GenerationContext) -> proc_macro2::TokenStream {
     let fsm_inner = &ctx.idents.fsm_inner;
     let action = &ctx.idents.action_trait;
+    let observer = &ctx.idents.observer_trait;
     let event_enum = &ctx.idents.event_enum;
-
-    let event_body = if let Some(log_level) = ctx.options.log_level {
-        let level = log_level_token(log_level);
-        let log_transition = format! {"{}: {{}} -[{{}}]-> {{}}, entering {{}}", ctx.fsm.name()};
-        quote::quote! {
-            let event_name = format!("{}", event);
-            if let Some(transition_state) = (self.current_state.transition)(event, &mut self.actions) {
-                let enter_state = (transition_state.enter_state)();
-                ::log::log!(#level, #log_transition,
-                    self.current_state.id,
-                    event_name,
-                    transition_state.id,
-                    enter_state.id
-                );
-                self.change_state(enter_state);
-                return true;
-            }
-            false
-        }
-    } else {
-        quote::quote! {
-            if let Some(transition_state) = (self.current_state.transition)(event, &mut self.actions) {
-                let enter_state = (transition_state.enter_state)();
-                self.change_state(enter_state);
-                return true;
-            }
-            false
-        }
-    };
+    let transition_enum = &ctx.idents.transition_enum;
+    let outcome_enum = &ctx.idents.outcome_enum;
+    let error_type = &ctx.fallible.error_type;
+    let fn_keyword = &ctx.asyncness.fn_keyword;
+    let await_op = &ctx.asyncness.await_op;
+    let posted = &ctx.posted.fsm_arg;
+
+    let log_transition = ctx.logging.log(
+        "{} -[{}]-> {}, entering {}",
+        quote::quote! { self.current_state.id, event_kind, transition_state.id, enter_state.id },
+    );
+    let log_guard_rejected = ctx.logging.log(
+        "{} rejects {} by its guards",
+        quote::quote! { self.current_state.id, event_kind },
+    );
+    let log_ignored = ctx.logging.log(
+        "{} ignores {}",
+        quote::quote! { self.current_state.id, event_kind },
+    );
 
     let entry_point = &ctx.deferred.entry_point;
 
     quote::quote! {
-        impl<A> #fsm_inner<A>
+        impl<A, O> #fsm_inner<A, O>
         where
             A: #action,
+            O: #observer,
         {
             #entry_point
 
-            fn try_event_based_transition(&mut self, event: #event_enum<A>) -> bool {
-                #event_body
+            #fn_keyword fn try_event_based_transition(&mut self, event: #event_enum<A>) -> Result<#outcome_enum, #error_type> {
+                let event_kind = event.kind();
+                let outcome = match (self.current_state.transition)(event, &mut self.actions, &mut self.observer, #posted)#await_op? {
+                    #transition_enum::To(transition_state) => {
+                        let enter_state = (transition_state.enter_state)();
+                        #log_transition
+                        let from = self.current_state.id;
+                        self.change_state(enter_state)#await_op?;
+                        #outcome_enum::Transitioned { from, to: self.current_state.id }
+                    }
+                    #transition_enum::Internal => #outcome_enum::Internal,
+                    #transition_enum::GuardRejected => {
+                        #log_guard_rejected
+                        self.actions.on_unhandled(self.current_state.id, event_kind);
+                        #outcome_enum::GuardRejected
+                    }
+                    #transition_enum::Ignored => {
+                        #log_ignored
+                        self.actions.on_unhandled(self.current_state.id, event_kind);
+                        #outcome_enum::Ignored
+                    }
+                };
+                Ok(outcome)
             }
         }
     }
 }
 
-fn log_level_token(level: log::Level) -> proc_macro2::TokenStream {
-    match level {
-        log::Level::Error => quote::quote! {log::Level::Error},
-        log::Level::Warn => quote::quote! {log::Level::Warn},
-        log::Level::Info => quote::quote! {log::Level::Info},
-        log::Level::Debug => quote::quote! {log::Level::Debug},
-        log::Level::Trace => quote::quote! {log::Level::Trace},
-    }
-}
-
-fn generate_direct_transition(state: &crate::fsm::State<'_>) -> proc_macro2::TokenStream {
+fn generate_direct_transition(
+    ctx: &GenerationContext,
+    state: &crate::fsm::State<'_>,
+) -> proc_macro2::TokenStream {
+    let try_op = &ctx.fallible.try_op;
+    let await_op = &ctx.asyncness.await_op;
+    let posted = &ctx.posted.state_param;
     let direct_transitions: Vec<_> = state
         .transitions()
         .filter(|t| t.event.is_none() && t.destination.is_some())
         .collect();
 
     if direct_transitions.is_empty() {
-        return quote::quote! { |_action| None };
+        let no_transition = ctx.asyncness.future(quote::quote! { Ok(None) });
+        let unused_posted = &ctx.posted.unused_state_param;
+        return quote::quote! { |_action, _observer, #unused_posted| #no_transition };
     }
 
     let all_guarded = direct_transitions.iter().all(|t| t.guard.is_some());
@@ -460,103 +914,171 @@ fn generate_direct_transition(state: &crate::fsm::State<'_>) -> proc_macro2::Tok
 
             let action = if let Some(a) = t.action {
                 let action_ident = a.ident();
-                quote::quote! { action.#action_ident(); }
+                let effect = observed_effect(ctx, a);
+                quote::quote! {
+                    #effect
+                    action.#action_ident(#posted)#await_op #try_op;
+                }
             } else {
                 quote::quote! {}
             };
 
             if let Some(g) = t.guard {
                 let guard_ident = g.ident();
+                let guard =
+                    observed_guard(ctx, g, quote::quote! { action.#guard_ident()#await_op });
                 quote::quote! {
-                    if action.#guard_ident() {
+                    if #guard {
                         #action
-                        return Some(Self::#dest_fn());
+                        return Ok(Some(Self::#dest_fn()));
                     }
                 }
             } else {
                 quote::quote! {
                     #action
-                    return Some(Self::#dest_fn());
+                    return Ok(Some(Self::#dest_fn()));
                 }
             }
         })
         .collect();
 
     let fallback = if all_guarded {
-        quote::quote! { None }
+        quote::quote! { Ok(None) }
     } else {
         quote::quote! {}
     };
 
+    let body = ctx.asyncness.future(quote::quote! {
+        #(#branches)*
+        #fallback
+    });
+    quote::quote! {
+        |action, observer, #posted| #body
+    }
+}
+
+/// Evaluates the guard and reports its result to the observer
+fn observed_guard(
+    ctx: &GenerationContext,
+    guard: &crate::fsm::Action,
+    call: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let guard_name = &guard.0;
+    let trace = ctx
+        .tracing
+        .event(quote::quote! { guard = #guard_name, result }, "guard");
     quote::quote! {
-        |action| {
-            #(#branches)*
-            #fallback
+        {
+            let result = #call;
+            observer.on_guard(#guard_name, result);
+            #trace
+            result
         }
     }
 }
 
+/// Reports the action of a transition to the observer, before it is run
+fn observed_effect(
+    ctx: &GenerationContext,
+    action: &crate::fsm::Action,
+) -> proc_macro2::TokenStream {
+    let action_name = &action.0;
+    let trace = ctx
+        .tracing
+        .event(quote::quote! { action = #action_name }, "effect");
+    quote::quote! {
+        observer.on_effect(#action_name);
+        #trace
+    }
+}
+
 fn generate_enter_action(
+    ctx: &GenerationContext,
     state: &crate::fsm::State<'_>,
-    state_id_enum: &proc_macro2::Ident,
 ) -> proc_macro2::TokenStream {
+    let try_op = &ctx.fallible.try_op;
+    let await_op = &ctx.asyncness.await_op;
+    let posted = &ctx.posted.state_param;
     let enter_action = if let Some(action) = state.enter_action() {
         let action_ident = action.ident();
         quote::quote! {
-            actions.#action_ident();
+            actions.#action_ident(#posted)#await_op #try_op;
         }
     } else {
         quote::quote! {}
     };
-    let internal_guard = generate_internal_transition_guard(state, state_id_enum, true);
+    let internal_guard = generate_internal_transition_guard(ctx, state, true);
     let parent_enter = if let Some(parent) = state.parent() {
         let parent_fn = parent.function_ident();
         quote::quote! {
-        (Self::#parent_fn().enter)(actions, from);
+        (Self::#parent_fn().enter)(actions, observer, #posted from)#await_op?;
         }
     } else {
         quote::quote! {}
     };
 
-    quote::quote! {
-        |actions, from|
-        {
+    let state_id_enum = &ctx.idents.state_id_enum;
+    let state_id_variant = state.state_id_variant_ident();
+    let trace_enter = ctx.tracing.event(
+        quote::quote! { state = %#state_id_enum::#state_id_variant },
+        "enter",
+    );
+
+    let body = ctx.asyncness.future(quote::quote! {
         #internal_guard
         #parent_enter
+        observer.on_enter(#state_id_enum::#state_id_variant);
+        #trace_enter
         #enter_action
-        }
+        Ok(())
+    });
+    quote::quote! {
+        |actions, observer, #posted from| #body
     }
 }
 
 fn generate_exit_action(
+    ctx: &GenerationContext,
     state: &crate::fsm::State<'_>,
-    state_id_enum: &proc_macro2::Ident,
 ) -> proc_macro2::TokenStream {
+    let try_op = &ctx.fallible.try_op;
+    let await_op = &ctx.asyncness.await_op;
+    let posted = &ctx.posted.state_param;
     let exit_action = if let Some(action) = state.exit_action() {
         let action_ident = action.ident();
         quote::quote! {
-            actions.#action_ident();
+            actions.#action_ident(#posted)#await_op #try_op;
         }
     } else {
         quote::quote! {}
     };
-    let internal_guard = generate_internal_transition_guard(state, state_id_enum, false);
+    let internal_guard = generate_internal_transition_guard(ctx, state, false);
     let parent_exit = if let Some(parent) = state.parent() {
         let parent_fn = parent.function_ident();
         quote::quote! {
-        (Self::#parent_fn().exit)(actions, to);
+        (Self::#parent_fn().exit)(actions, observer, #posted to)#await_op?;
         }
     } else {
         quote::quote! {}
     };
 
-    quote::quote! {
-        |actions, to|
-        {
+    let state_id_enum = &ctx.idents.state_id_enum;
+    let state_id_variant = state.state_id_variant_ident();
+    let trace_exit = ctx.tracing.event(
+        quote::quote! { state = %#state_id_enum::#state_id_variant },
+        "exit",
+    );
+
+    let body = ctx.asyncness.future(quote::quote! {
         #internal_guard
+        observer.on_exit(#state_id_enum::#state_id_variant);
+        #trace_exit
         #exit_action
         #parent_exit
-        }
+        Ok(())
+    });
+    quote::quote! {
+        |actions, observer, #posted to| #body
     }
 }
 
@@ -574,11 +1096,11 @@ fn all_substate_ids(
 }
 
 fn generate_internal_transition_guard(
+    ctx: &GenerationContext,
     state: &crate::fsm::State<'_>,
-    state_id_enum: &proc_macro2::Ident,
     is_enter: bool,
 ) -> proc_macro2::TokenStream {
-    let substate_ids = all_substate_ids(state, state_id_enum);
+    let substate_ids = all_substate_ids(state, &ctx.idents.state_id_enum);
     if substate_ids.is_empty() {
         quote::quote! {}
     } else {
@@ -588,8 +1110,8 @@ fn generate_internal_transition_guard(
             quote::quote! {to}
         };
         quote::quote! {
-            if matches!(#check.id, #(#substate_ids)|*) {
-                return;
+            if matches!(#check, #(#substate_ids)|*) {
+                return Ok(());
             }
         }
     }
@@ -9,9 +9,17 @@ pub struct Idents {
     pub module: proc_macro2::Ident,
     pub event_params_trait: proc_macro2::Ident,
     pub event_enum: proc_macro2::Ident,
+    pub event_kind_enum: proc_macro2::Ident,
     pub action_trait: proc_macro2::Ident,
+    pub observer_trait: proc_macro2::Ident,
     pub state_struct: proc_macro2::Ident,
     pub state_id_enum: proc_macro2::Ident,
+    pub transition_enum: proc_macro2::Ident,
+    pub outcome_enum: proc_macro2::Ident,
+    pub deferred_queue: proc_macro2::Ident,
+    pub posted_events: proc_macro2::Ident,
+    pub snapshot_struct: proc_macro2::Ident,
+    pub restore_error: proc_macro2::Ident,
     pub init_state_id_variant: proc_macro2::Ident,
 }
 
@@ -19,14 +27,22 @@ impl Idents {
     pub fn new(name: &str) -> Idents {
         let name = name.to_string();
         Idents {
-            fsm: quote::format_ident!("{}", name.to_upper_camel_case()),
+            fsm: ident(name.to_upper_camel_case()),
             fsm_inner: quote::format_ident!("{}Inner", name.to_upper_camel_case()),
-            module: quote::format_ident!("{}", name.to_snake_case()),
+            module: ident(name.to_snake_case()),
             event_params_trait: quote::format_ident!("I{}EventParams", name.to_upper_camel_case()),
             event_enum: quote::format_ident!("{}Event", name.to_upper_camel_case()),
+            event_kind_enum: quote::format_ident!("{}EventKind", name.to_upper_camel_case()),
             action_trait: quote::format_ident!("I{}Actions", name.to_upper_camel_case()),
+            observer_trait: quote::format_ident!("I{}Observer", name.to_upper_camel_case()),
             state_struct: quote::format_ident!("{}State", name.to_upper_camel_case()),
             state_id_enum: quote::format_ident!("{}StateId", name.to_upper_camel_case()),
+            transition_enum: quote::format_ident!("{}Transition", name.to_upper_camel_case()),
+            outcome_enum: quote::format_ident!("{}Outcome", name.to_upper_camel_case()),
+            deferred_queue: quote::format_ident!("{}DeferredQueue", name.to_upper_camel_case()),
+            posted_events: quote::format_ident!("{}PostedEvents", name.to_upper_camel_case()),
+            snapshot_struct: quote::format_ident!("{}Snapshot", name.to_upper_camel_case()),
+            restore_error: quote::format_ident!("{}RestoreError", name.to_upper_camel_case()),
             init_state_id_variant: quote::format_ident!(
                 "_{}InitialState_",
                 name.to_upper_camel_case()
@@ -37,44 +53,36 @@ impl Idents {
 
 impl Event {
     pub fn params_ident(&self) -> proc_macro2::Ident {
-        quote::format_ident!("{}Params", self.0.to_upper_camel_case())
+        ident(format!("{}Params", self.0.to_upper_camel_case()))
     }
 
     pub fn ident(&self) -> proc_macro2::Ident {
-        quote::format_ident!("{}", self.0.to_upper_camel_case())
+        ident(self.0.to_upper_camel_case())
     }
 
     pub fn method_ident(&self) -> proc_macro2::Ident {
-        quote::format_ident!("{}", self.0.to_snake_case())
+        ident(self.0.to_snake_case())
     }
 }
 
 impl Action {
     pub fn ident(&self) -> proc_macro2::Ident {
-        quote::format_ident!("{}", self.0.to_snake_case())
+        ident(self.0.to_snake_case())
     }
 }
 
 impl fsm::State<'_> {
     pub fn function_ident(&self) -> proc_macro2::Ident {
-        quote::format_ident!("{}", self.qualified_name("_").to_snake_case())
+        ident(self.qualified_name("_").to_snake_case())
     }
 
     pub fn state_id_variant_ident(&self) -> proc_macro2::Ident {
-        quote::format_ident!("{}", self.qualified_name("").to_upper_camel_case())
+        ident(self.qualified_name("").to_upper_camel_case())
     }
 
     pub fn name_literal(&self) -> proc_macro2::Literal {
         proc_macro2::Literal::string(&self.qualified_name("::"))
     }
-
-    fn qualified_name(&self, separator: impl Into<String>) -> String {
-        use itertools::Itertools;
-        let names: Vec<_> = std::iter::successors(Some(self.clone()), |next| next.parent())
-            .map(|s| s.name().to_string())
-            .collect();
-        Itertools::intersperse(names.into_iter().rev(), separator.into()).collect()
-    }
 }
 
 impl quote::ToTokens for fsm::State<'_> {
@@ -82,3 +90,35 @@ impl quote::ToTokens for fsm::State<'_> {
         self.name().to_tokens(tokens);
     }
 }
+
+/// Strict and reserved keywords of all editions
+const KEYWORDS: [&str; 52] = [
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "Self", "static", "struct", "super", "trait", "true", "try", "type",
+    "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// Keywords which cannot be used as raw identifiers either
+const NON_RAW_KEYWORDS: [&str; 5] = ["crate", "self", "Self", "super", "_"];
+
+/// Maps a name to a Rust identifier. Keywords are escaped as raw identifiers, or suffixed with an
+/// underscore if they cannot be raw. Returns `None` if the name is no valid identifier.
+pub fn try_ident(name: &str) -> Option<proc_macro2::Ident> {
+    let span = proc_macro2::Span::call_site();
+    if NON_RAW_KEYWORDS.contains(&name) {
+        Some(quote::format_ident!("{}_", name))
+    } else if KEYWORDS.contains(&name) {
+        Some(proc_macro2::Ident::new_raw(name, span))
+    } else {
+        syn::parse_str::<syn::Ident>(name)
+            .ok()
+            .map(|_| proc_macro2::Ident::new(name, span))
+    }
+}
+
+/// Names are validated before code generation, see [`super::naming::validate`]
+fn ident(name: String) -> proc_macro2::Ident {
+    try_ident(&name).unwrap_or_else(|| panic!("'{name}' is not a valid Rust identifier"))
+}
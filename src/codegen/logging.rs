@@ -0,0 +1,204 @@
+use super::ident;
+
+pub const DEFAULT_FORMAT: &str = "{fsm}: {message}";
+
+/// The crate logging the steps of the FSM
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Logger {
+    #[default]
+    Log,
+    /// Logs with `defmt` on embedded targets, the state ids and event kinds implement its `Format`
+    Defmt,
+}
+
+pub struct LoggingCodegen {
+    /// Formatting of the state ids and event kinds for the logger, if needed. They are formatted
+    /// by their names in the diagram, as by their `Display` for `log`.
+    pub state_id_format: proc_macro2::TokenStream,
+    pub event_kind_format: proc_macro2::TokenStream,
+    level: Option<log::Level>,
+    logger: Logger,
+    target: Option<String>,
+    /// The format string of the logging macros, with `{message}` still to be replaced
+    format: String,
+}
+
+impl LoggingCodegen {
+    pub fn new(
+        fsm_name: &str,
+        idents: &ident::Idents,
+        level: Option<log::Level>,
+        logger: Logger,
+        target: Option<&str>,
+        format: Option<&str>,
+    ) -> Self {
+        // Braces of the configured format are literal, only the placeholders are replaced
+        let format = format
+            .unwrap_or(DEFAULT_FORMAT)
+            .replace('{', "{{")
+            .replace('}', "}}")
+            .replace("{{fsm}}", fsm_name);
+        let (state_id_format, event_kind_format) = match (level, logger) {
+            (Some(_), Logger::Defmt) => {
+                let state_id_enum = &idents.state_id_enum;
+                let event_kind_enum = &idents.event_kind_enum;
+                (
+                    defmt_format_impl(state_id_enum, quote::quote! { <&'static str>::from(*self) }),
+                    defmt_format_impl(event_kind_enum, quote::quote! { self.name() }),
+                )
+            }
+            _ => (quote::quote! {}, quote::quote! {}),
+        };
+        Self {
+            state_id_format,
+            event_kind_format,
+            level,
+            logger,
+            target: target.map(str::to_string),
+            format,
+        }
+    }
+
+    /// Logs a step of the FSM. The message is a format string of the arguments.
+    pub fn log(&self, message: &str, args: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        let Some(level) = self.level else {
+            return quote::quote! {};
+        };
+        let format = self.format.replace("{{message}}", message);
+        match self.logger {
+            Logger::Log => {
+                let level = log_level_token(level);
+                let target = self
+                    .target
+                    .as_ref()
+                    .map(|target| quote::quote! { target: #target, });
+                quote::quote! { ::log::log!(#target #level, #format, #args); }
+            }
+            Logger::Defmt => {
+                let macro_ident = defmt_macro_ident(level);
+                quote::quote! { ::defmt::#macro_ident!(#format, #args); }
+            }
+        }
+    }
+}
+
+/// Formats the type with `defmt` by the name, which is a `&'static str`
+fn defmt_format_impl(
+    ident: &proc_macro2::Ident,
+    name: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    quote::quote! {
+        impl ::defmt::Format for #ident {
+            fn format(&self, f: ::defmt::Formatter<'_>) {
+                ::defmt::write!(f, "{=str}", #name)
+            }
+        }
+    }
+}
+
+fn log_level_token(level: log::Level) -> proc_macro2::TokenStream {
+    match level {
+        log::Level::Error => quote::quote! { ::log::Level::Error },
+        log::Level::Warn => quote::quote! { ::log::Level::Warn },
+        log::Level::Info => quote::quote! { ::log::Level::Info },
+        log::Level::Debug => quote::quote! { ::log::Level::Debug },
+        log::Level::Trace => quote::quote! { ::log::Level::Trace },
+    }
+}
+
+fn defmt_macro_ident(level: log::Level) -> proc_macro2::Ident {
+    let name = match level {
+        log::Level::Error => "error",
+        log::Level::Warn => "warn",
+        log::Level::Info => "info",
+        log::Level::Debug => "debug",
+        log::Level::Trace => "trace",
+    };
+    quote::format_ident!("{name}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codegen(logger: Logger, format: Option<&str>) -> LoggingCodegen {
+        let idents = ident::Idents::new("Door");
+        LoggingCodegen::new(
+            "Door",
+            &idents,
+            Some(log::Level::Warn),
+            logger,
+            None,
+            format,
+        )
+    }
+
+    /// The arguments of the logging macro, i.e. the format string and its arguments
+    fn message_of(tokens: proc_macro2::TokenStream) -> String {
+        let group = tokens
+            .into_iter()
+            .find_map(|token| match token {
+                proc_macro2::TokenTree::Group(group) => Some(group.stream().to_string()),
+                _ => None,
+            })
+            .unwrap();
+        group
+            .strip_prefix(":: log :: Level :: Warn , ")
+            .unwrap_or(&group)
+            .to_string()
+    }
+
+    #[test]
+    fn braces_of_the_format_are_literal() {
+        let logging = codegen(Logger::Log, Some("{fsm} {x}: {message}"));
+        let tokens = logging.log("{} opened", quote::quote! { state });
+        assert!(tokens.to_string().contains(r#""Door {{x}}: {} opened""#));
+    }
+
+    #[test]
+    fn defmt_logs_with_the_macro_of_the_level() {
+        let logging = codegen(Logger::Defmt, None);
+        let tokens = logging.log("{} opened", quote::quote! { state });
+        assert_eq!(
+            tokens.to_string(),
+            quote::quote! { ::defmt::warn!("Door: {} opened", state); }.to_string()
+        );
+    }
+
+    #[test]
+    fn both_loggers_pass_the_same_messages() {
+        let log = codegen(Logger::Log, Some("<{fsm}> {message}"));
+        let defmt = codegen(Logger::Defmt, Some("<{fsm}> {message}"));
+        let args = quote::quote! { self.current_state.id, event.kind() };
+
+        assert_eq!(
+            message_of(log.log("{} ignores {}", args.clone())),
+            message_of(defmt.log("{} ignores {}", args))
+        );
+    }
+
+    #[test]
+    fn defmt_formats_the_names_of_the_diagram() {
+        let logging = codegen(Logger::Defmt, None);
+
+        // The same names as by `Display` of the state ids and event kinds, which `log` uses
+        assert_eq!(
+            logging.state_id_format.to_string(),
+            quote::quote! {
+                impl ::defmt::Format for DoorStateId {
+                    fn format(&self, f: ::defmt::Formatter<'_>) {
+                        ::defmt::write!(f, "{=str}", <&'static str>::from(*self))
+                    }
+                }
+            }
+            .to_string()
+        );
+        assert!(
+            logging
+                .event_kind_format
+                .to_string()
+                .contains("self . name ()")
+        );
+        assert!(codegen(Logger::Log, None).state_id_format.is_empty());
+    }
+}
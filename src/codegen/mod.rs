@@ -1,15 +1,42 @@
+mod asyncness;
 mod deferred;
 mod extract;
+mod fallible;
 mod generators;
 mod ident;
+mod logging;
+mod naming;
+mod posted;
+mod snapshot;
+mod tracing;
 
+pub use logging::Logger;
+
+use crate::error::Result;
 use crate::fsm;
 
 type GeneratedCode = proc_macro2::TokenStream;
 
-#[derive(Default, Debug, Copy, Clone)]
+#[derive(Default, Debug, Clone)]
 pub struct Options {
     pub log_level: Option<log::Level>,
+    pub logger: Logger,
+    /// Target of the log records, instead of the module path of the FSM
+    pub log_target: Option<String>,
+    /// Format of the log messages, with the placeholders `{fsm}` and `{message}`
+    pub log_format: Option<String>,
+    /// Level of the spans and events of the `tracing` crate
+    pub tracing_level: Option<log::Level>,
+    /// Actions return a `Result`, failures are routed by the `error` transitions
+    pub fallible_actions: bool,
+    /// Actions, guards and event methods are `async`
+    pub async_actions: bool,
+    /// Capacity of the inline queue of deferred events, instead of a queue on the heap
+    pub deferred_capacity: Option<usize>,
+    /// Capacity of the queue of the events posted by the actions, which enables posting
+    pub posted_capacity: Option<usize>,
+    /// Generates `snapshot()` and `restore()`, serialized with serde
+    pub serde: bool,
 }
 
 pub struct FsmCodeGenerator {
@@ -18,50 +45,128 @@ pub struct FsmCodeGenerator {
 
 impl FsmCodeGenerator {
     pub fn new(options: &Options) -> Self {
-        Self { options: *options }
+        Self {
+            options: options.clone(),
+        }
     }
 
-    pub fn generate(&self, fsm: fsm::UmlFsm) -> GeneratedCode {
+    pub fn generate(&self, mut fsm: fsm::UmlFsm) -> Result<GeneratedCode> {
+        // Hashed before the error transitions are removed, so that they identify the diagram too
+        let diagram_hash = snapshot::diagram_hash(&fsm);
+        let asyncness = asyncness::AsyncActionsCodegen::new(self.options.async_actions);
+        let fallible = fallible::FallibleActionsCodegen::new(
+            &mut fsm,
+            self.options.fallible_actions,
+            &asyncness,
+        )?;
+        naming::validate(&fsm)?;
+
         let idents = ident::Idents::new(fsm.name());
-        let deferred = deferred::DeferredEventsCodegen::new(&fsm, &idents);
+        let tracing =
+            tracing::TracingCodegen::new(fsm.name(), self.options.tracing_level, &asyncness);
+        let logging = logging::LoggingCodegen::new(
+            fsm.name(),
+            &idents,
+            self.options.log_level,
+            self.options.logger,
+            self.options.log_target.as_deref(),
+            self.options.log_format.as_deref(),
+        );
+        let deferred = deferred::DeferredEventsCodegen::new(
+            &fsm,
+            &idents,
+            &fallible,
+            &asyncness,
+            &tracing,
+            &logging,
+            self.options.deferred_capacity,
+        );
+        let posted = posted::PostedEventsCodegen::new(
+            &idents,
+            &fallible,
+            &asyncness,
+            &tracing,
+            &logging,
+            &deferred,
+            self.options.posted_capacity,
+        );
+        let snapshot = snapshot::SnapshotCodegen::new(
+            &fsm,
+            &idents,
+            &deferred,
+            &posted,
+            diagram_hash,
+            self.options.serde,
+        );
         let ctx = GenerationContext {
             fsm: &fsm,
             deferred: &deferred,
+            posted: &posted,
+            snapshot: &snapshot,
+            tracing: &tracing,
+            logging: &logging,
+            fallible: &fallible,
+            asyncness: &asyncness,
             idents: &idents,
-            options: &self.options,
         };
 
         let event_params_trait = generators::generate_event_params_trait(&ctx);
         let action_trait = generators::generate_action_trait(&ctx);
+        let observer_trait = generators::generate_observer_trait(&ctx);
         let event_enum = generators::generate_event_enum(&ctx);
+        let event_kind_enum = generators::generate_event_kind_enum(&ctx);
         let event_enum_display = generators::generate_event_enum_display(&ctx);
         let state_id_enum = generators::generate_state_id_enum(&ctx);
+        let outcome_enum = generators::generate_outcome_enum(&ctx);
         let state_struct = generators::generate_state_struct(&ctx);
         let state_impl = generators::generate_state_impl(&ctx);
         let fsm = generators::generate_fsm(&ctx);
 
+        // Besides the heap, the generated code only depends on `core`
+        let alloc_crate = if deferred.needs_alloc || asyncness.needs_alloc() || snapshot.needs_alloc
+        {
+            quote::quote! { extern crate alloc; }
+        } else {
+            quote::quote! {}
+        };
+        let deferred_queue = &deferred.queue;
+        let posted_queue = &posted.queue;
+        let snapshot_items = &snapshot.items;
+
         let module_name = &idents.module;
-        quote::quote! {
+        Ok(quote::quote! {
             mod #module_name {
+                #alloc_crate
                 pub type NoEventData = ();
                 #event_params_trait
                 #action_trait
+                #observer_trait
                 #event_enum
+                #event_kind_enum
                 #event_enum_display
                 #state_id_enum
+                #outcome_enum
                 #state_struct
                 #state_impl
+                #deferred_queue
+                #posted_queue
                 #fsm
+                #snapshot_items
             }
-        }
+        })
     }
 }
 
 pub struct GenerationContext<'a> {
     pub fsm: &'a fsm::UmlFsm,
     pub deferred: &'a deferred::DeferredEventsCodegen,
+    pub posted: &'a posted::PostedEventsCodegen,
+    pub snapshot: &'a snapshot::SnapshotCodegen,
+    pub tracing: &'a tracing::TracingCodegen,
+    pub logging: &'a logging::LoggingCodegen,
+    pub fallible: &'a fallible::FallibleActionsCodegen,
+    pub asyncness: &'a asyncness::AsyncActionsCodegen,
     pub idents: &'a ident::Idents,
-    pub options: &'a Options,
 }
 
 #[cfg(test)]
@@ -80,7 +185,7 @@ mod tests {
     ) -> std::path::PathBuf {
         let generator = FsmCodeGenerator::new(options);
 
-        let module_code = generator.generate(test_data.parsed);
+        let module_code = generator.generate(test_data.parsed).unwrap();
         let complete_code = format!("#![allow(warnings)] {module_code}\n\nfn main() {{}}\n");
 
         let base_name = format!("target/tests/data/codegen/{test_name}");
@@ -111,7 +216,69 @@ mod tests {
     fn all_generators_logging() {
         let options = Options {
             log_level: Some(log::Level::Info),
+            log_target: Some("fsm".to_string()),
+            log_format: Some("[{fsm}] {message}".to_string()),
+            ..Default::default()
         };
         test_all_generators_with_options(&options, "logging_options");
     }
+
+    #[test]
+    fn all_generators_fallible_actions() {
+        let options = Options {
+            fallible_actions: true,
+            ..Default::default()
+        };
+        test_all_generators_with_options(&options, "fallible_actions_options");
+    }
+
+    #[test]
+    fn all_generators_async_actions() {
+        let options = Options {
+            fallible_actions: true,
+            async_actions: true,
+            ..Default::default()
+        };
+        test_all_generators_with_options(&options, "async_actions_options");
+    }
+
+    #[test]
+    fn all_generators_deferred_capacity() {
+        let options = Options {
+            deferred_capacity: Some(2),
+            ..Default::default()
+        };
+        test_all_generators_with_options(&options, "deferred_capacity_options");
+    }
+
+    #[test]
+    fn all_generators_serde() {
+        let options = Options {
+            serde: true,
+            deferred_capacity: Some(2),
+            ..Default::default()
+        };
+        test_all_generators_with_options(&options, "serde_options");
+    }
+
+    #[test]
+    fn all_generators_tracing() {
+        let options = Options {
+            tracing_level: Some(log::Level::Debug),
+            ..Default::default()
+        };
+        test_all_generators_with_options(&options, "tracing_options");
+    }
+
+    #[test]
+    fn all_generators_posted_events() {
+        let options = Options {
+            posted_capacity: Some(2),
+            fallible_actions: true,
+            async_actions: true,
+            serde: true,
+            ..Default::default()
+        };
+        test_all_generators_with_options(&options, "posted_events_options");
+    }
 }
@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+
+use heck::{ToSnakeCase, ToUpperCamelCase};
+use itertools::Itertools;
+
+use crate::error::{Error, Result};
+use crate::fsm::UmlFsm;
+
+use super::{extract, ident};
+
+/// Names of generated functions of the state struct, which states must not map to
+const RESERVED_STATE_FUNCTIONS: [&str; 1] = ["init"];
+
+/// Names of generated methods of the FSM struct, which events must not map to
+const RESERVED_FSM_METHODS: [&str; 11] = [
+    "trigger",
+    "current_state",
+    "active_states",
+    "is_in",
+    "actions",
+    "actions_mut",
+    "observer",
+    "observer_mut",
+    "into_actions",
+    "stop",
+    "snapshot",
+];
+
+/// Names of generated methods of the actions trait, which actions must not map to
+const RESERVED_ACTION_METHODS: [&str; 1] = ["on_unhandled"];
+
+/// Checks that all names of the diagram map to valid and distinct Rust identifiers.
+pub fn validate(fsm: &UmlFsm) -> Result<()> {
+    let fsm_name = || std::iter::once(fsm.name().to_string());
+    check_unique("FSM", fsm_name(), |n| n.to_upper_camel_case(), &[])?;
+    check_unique("FSM", fsm_name(), |n| n.to_snake_case(), &[])?;
+
+    let events = || extract::events(fsm).map(|e| e.0.clone());
+    check_unique(
+        "Event",
+        events(),
+        |n| n.to_snake_case(),
+        &RESERVED_FSM_METHODS,
+    )?;
+    check_unique("Event", events(), |n| n.to_upper_camel_case(), &[])?;
+
+    let actions = extract::actions(fsm)
+        .map(|(action, _)| action)
+        .chain(extract::guards(fsm).map(|(guard, _)| guard))
+        .chain(extract::direct_transition_actions(fsm))
+        .chain(extract::direct_transition_guards(fsm))
+        .cloned()
+        .chain(extract::enter_actions(fsm))
+        .chain(extract::exit_actions(fsm))
+        .map(|a| a.0);
+    check_unique(
+        "Action",
+        actions,
+        |n| n.to_snake_case(),
+        &RESERVED_ACTION_METHODS,
+    )?;
+
+    let states = || fsm.states().map(|s| s.qualified_name("::"));
+    check_unique(
+        "State",
+        states(),
+        |n| n.replace("::", "_").to_snake_case(),
+        &RESERVED_STATE_FUNCTIONS,
+    )?;
+    check_unique(
+        "State",
+        states(),
+        |n| n.replace("::", "").to_upper_camel_case(),
+        &[],
+    )
+}
+
+fn check_unique(
+    kind: &str,
+    names: impl Iterator<Item = String>,
+    to_rust_name: impl Fn(&str) -> String,
+    reserved: &[&str],
+) -> Result<()> {
+    let mut mapped: HashMap<String, String> = HashMap::new();
+    for name in names.unique() {
+        let rust_name = to_rust_name(&name);
+        let ident = ident::try_ident(&rust_name).ok_or_else(|| {
+            Error::NameMapping(format!(
+                "{kind} name '{name}' maps to '{rust_name}', which is not a valid Rust identifier"
+            ))
+        })?;
+        if reserved.contains(&rust_name.as_str()) {
+            return Err(Error::NameMapping(format!(
+                "{kind} name '{name}' maps to the reserved Rust identifier '{rust_name}'"
+            )));
+        }
+        if let Some(other) = mapped.insert(ident.to_string(), name.clone()) {
+            return Err(Error::NameMapping(format!(
+                "{kind} names '{other}' and '{name}' both map to the Rust identifier '{ident}'"
+            )));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::validate;
+    use crate::error::{Error, Result};
+    use crate::fsm::{Action, Event, StateType, TransitionParameters, UmlFsm, UmlFsmBuilder};
+
+    fn fsm_with_transitions(transitions: &[(&str, &str, &str, &str)]) -> UmlFsm {
+        let mut builder = UmlFsmBuilder::new("Test");
+        builder.add_state(transitions[0].0, StateType::Enter);
+        for (source, target, event, action) in transitions {
+            builder.add_transition(TransitionParameters {
+                source,
+                target: Some(target),
+                event: Some(Event(event.to_string())),
+                action: Some(Action(action.to_string())),
+                guard: None,
+            });
+        }
+        builder.build().unwrap()
+    }
+
+    fn assert_name_mapping_error(result: Result<()>, expected: &str) {
+        match result {
+            Err(Error::NameMapping(msg)) => assert!(msg.contains(expected), "{msg}"),
+            other => panic!("Expected name mapping error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn keywords_and_unicode_are_valid() {
+        let fsm = fsm_with_transitions(&[
+            ("Überhitzt", "cool-down", "match", "type"),
+            ("cool-down", "Überhitzt", "go_to_a", "self"),
+        ]);
+        assert!(validate(&fsm).is_ok());
+    }
+
+    #[test]
+    fn colliding_event_names_are_rejected() {
+        let fsm = fsm_with_transitions(&[("A", "B", "GoToA", "Foo"), ("B", "A", "go_to_a", "Bar")]);
+        assert_name_mapping_error(
+            validate(&fsm),
+            "Event names 'GoToA' and 'go_to_a' both map to the Rust identifier 'go_to_a'",
+        );
+    }
+
+    #[test]
+    fn colliding_action_names_are_rejected() {
+        let fsm = fsm_with_transitions(&[("A", "B", "E1", "do-it"), ("B", "A", "E2", "DoIt")]);
+        assert_name_mapping_error(validate(&fsm), "both map to the Rust identifier 'do_it'");
+    }
+
+    #[test]
+    fn invalid_identifier_is_rejected() {
+        let fsm = fsm_with_transitions(&[("1st", "B", "Go", "Foo")]);
+        assert_name_mapping_error(validate(&fsm), "is not a valid Rust identifier");
+    }
+
+    #[test]
+    fn reserved_event_name_is_rejected() {
+        let fsm = fsm_with_transitions(&[("A", "B", "Trigger", "Foo")]);
+        assert_name_mapping_error(validate(&fsm), "reserved Rust identifier 'trigger'");
+    }
+
+    #[test]
+    fn reserved_state_name_is_rejected() {
+        let fsm = fsm_with_transitions(&[("Init", "B", "Go", "Foo")]);
+        assert_name_mapping_error(validate(&fsm), "reserved Rust identifier 'init'");
+    }
+}
@@ -0,0 +1,183 @@
+use super::asyncness::AsyncActionsCodegen;
+use super::deferred::DeferredEventsCodegen;
+use super::fallible::FallibleActionsCodegen;
+use super::ident;
+use super::logging::LoggingCodegen;
+use super::tracing::TracingCodegen;
+
+/// Events posted by the actions, which are processed after the event being processed, with
+/// run-to-completion semantics. Posting is bounded by the capacity of the inline queue.
+pub struct PostedEventsCodegen {
+    /// The queue of the posted events, a module level item
+    pub queue: proc_macro2::TokenStream,
+    /// Parameter of the actions, which post events through it
+    pub action_param: proc_macro2::TokenStream,
+    /// Argument of the actions, in the functions of the states
+    pub action_arg: proc_macro2::TokenStream,
+    /// Bound of the actions trait, as the posted events are generic over the actions
+    pub sized_bound: proc_macro2::TokenStream,
+    /// Parameter of the function pointers of the states
+    pub state_param_type: proc_macro2::TokenStream,
+    pub state_param: proc_macro2::TokenStream,
+    pub unused_state_param: proc_macro2::TokenStream,
+    /// Argument of the function pointers of the states, in the methods of the FSM
+    pub fsm_arg: proc_macro2::TokenStream,
+    pub fsm_field: proc_macro2::TokenStream,
+    pub fsm_init_field: proc_macro2::TokenStream,
+    pub fsm_methods: proc_macro2::TokenStream,
+    enabled: bool,
+    await_op: proc_macro2::TokenStream,
+}
+
+impl PostedEventsCodegen {
+    pub fn new(
+        idents: &ident::Idents,
+        fallible: &FallibleActionsCodegen,
+        asyncness: &AsyncActionsCodegen,
+        tracing: &TracingCodegen,
+        logging: &LoggingCodegen,
+        deferred: &DeferredEventsCodegen,
+        capacity: Option<usize>,
+    ) -> Self {
+        match capacity {
+            Some(capacity) => Self::enabled(
+                idents, fallible, asyncness, tracing, logging, deferred, capacity,
+            ),
+            None => Self::disabled(asyncness),
+        }
+    }
+
+    fn disabled(asyncness: &AsyncActionsCodegen) -> Self {
+        Self {
+            queue: quote::quote! {},
+            action_param: quote::quote! {},
+            action_arg: quote::quote! {},
+            sized_bound: quote::quote! {},
+            state_param_type: quote::quote! {},
+            state_param: quote::quote! {},
+            unused_state_param: quote::quote! {},
+            fsm_arg: quote::quote! {},
+            fsm_field: quote::quote! {},
+            fsm_init_field: quote::quote! {},
+            fsm_methods: quote::quote! {},
+            enabled: false,
+            await_op: asyncness.await_op.clone(),
+        }
+    }
+
+    fn enabled(
+        idents: &ident::Idents,
+        fallible: &FallibleActionsCodegen,
+        asyncness: &AsyncActionsCodegen,
+        tracing: &TracingCodegen,
+        logging: &LoggingCodegen,
+        deferred: &DeferredEventsCodegen,
+        capacity: usize,
+    ) -> Self {
+        let queue = &idents.posted_events;
+        let actions_trait = &idents.action_trait;
+        let event_enum = &idents.event_enum;
+        let error_type = &fallible.error_type;
+        let fn_keyword = &asyncness.fn_keyword;
+        let await_op = &asyncness.await_op;
+        let entry_method = &deferred.entry_method;
+        let process_posted_event = tracing.instrument(
+            "posted",
+            quote::quote! {
+                let result = self.#entry_method(event)#await_op;
+                self.recover(result)#await_op
+            },
+        );
+        let log_posted = logging.log(
+            "{} processes posted {}",
+            quote::quote! { self.current_state.id, event.kind() },
+        );
+        let post_doc = format!(
+            " Posts the event, which is processed after the current one. At most {capacity} events\n\
+             are posted for an event triggered on the FSM, further ones are returned, so that\n\
+             actions cannot post events endlessly."
+        );
+        Self {
+            queue: quote::quote! {
+                /// The events posted by the actions, which are processed after the current event
+                pub struct #queue<A: #actions_trait> {
+                    events: [Option<#event_enum<A>>; #capacity],
+                    next: usize,
+                    posted: usize,
+                }
+
+                impl<A: #actions_trait> #queue<A> {
+                    fn new() -> Self {
+                        Self {
+                            events: [const { None }; #capacity],
+                            next: 0,
+                            posted: 0,
+                        }
+                    }
+
+                    #[doc = #post_doc]
+                    pub fn post(&mut self, event: #event_enum<A>) -> Result<(), #event_enum<A>> {
+                        if self.posted == #capacity {
+                            return Err(event);
+                        }
+                        self.events[self.posted] = Some(event);
+                        self.posted += 1;
+                        Ok(())
+                    }
+
+                    fn pop_front(&mut self) -> Option<#event_enum<A>> {
+                        let event = self.events.get_mut(self.next)?.take()?;
+                        self.next += 1;
+                        Some(event)
+                    }
+
+                    /// Discards the events not processed yet, and allows to post events again
+                    fn clear(&mut self) {
+                        while self.pop_front().is_some() {}
+                        self.next = 0;
+                        self.posted = 0;
+                    }
+                }
+            },
+            action_param: quote::quote! { posted: &mut #queue<Self>, },
+            action_arg: quote::quote! { posted, },
+            sized_bound: quote::quote! { + Sized },
+            state_param_type: quote::quote! { &'a mut #queue<A>, },
+            state_param: quote::quote! { posted, },
+            unused_state_param: quote::quote! { _posted, },
+            fsm_arg: quote::quote! { &mut self.posted_events, },
+            fsm_field: quote::quote! { posted_events: #queue<A>, },
+            fsm_init_field: quote::quote! { posted_events: #queue::new(), },
+            fsm_methods: quote::quote! {
+                /// Processes the events posted by the actions, in the order of posting
+                #fn_keyword fn process_posted(&mut self) -> Result<(), #error_type> {
+                    while let Some(event) = self.posted_events.pop_front() {
+                        #log_posted
+                        let result = { #process_posted_event };
+                        // Only the outcome of the triggered event is reported
+                        let _ = result?;
+                    }
+                    Ok(())
+                }
+            },
+            enabled: true,
+            await_op: await_op.clone(),
+        }
+    }
+
+    /// Processes the posted events after `result` of an event triggered on the FSM `target`.
+    /// After an error, the posted events are discarded.
+    pub fn run_to_completion(&self, target: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        if !self.enabled {
+            return quote::quote! {};
+        }
+        let await_op = &self.await_op;
+        quote::quote! {
+            let result = match result {
+                Ok(value) => #target.process_posted()#await_op.map(|()| value),
+                Err(error) => Err(error),
+            };
+            #target.posted_events.clear();
+        }
+    }
+}
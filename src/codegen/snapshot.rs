@@ -0,0 +1,315 @@
+use crate::fsm;
+
+use super::deferred::DeferredEventsCodegen;
+use super::posted::PostedEventsCodegen;
+use super::{extract, ident};
+
+pub struct SnapshotCodegen {
+    /// Added to the derives of the state id enum
+    pub state_id_derive: proc_macro2::TokenStream,
+    /// Attributes of the event enum, serialized as part of the deferred events
+    pub event_attributes: proc_macro2::TokenStream,
+    /// Functions of the state struct
+    pub state_functions: proc_macro2::TokenStream,
+    /// Methods of the FSM struct
+    pub fsm_methods: proc_macro2::TokenStream,
+    /// The snapshot type, its error and `restore()`, module level items
+    pub items: proc_macro2::TokenStream,
+    /// The deferred events of a snapshot are collected into a `Vec`
+    pub needs_alloc: bool,
+}
+
+impl SnapshotCodegen {
+    pub fn new(
+        fsm: &fsm::UmlFsm,
+        idents: &ident::Idents,
+        deferred: &DeferredEventsCodegen,
+        posted: &PostedEventsCodegen,
+        diagram_hash: u64,
+        serde: bool,
+    ) -> Self {
+        if serde {
+            Self::enabled(fsm, idents, deferred, posted, diagram_hash)
+        } else {
+            Self::disabled()
+        }
+    }
+
+    fn disabled() -> Self {
+        Self {
+            state_id_derive: quote::quote! {},
+            event_attributes: quote::quote! {},
+            state_functions: quote::quote! {},
+            fsm_methods: quote::quote! {},
+            items: quote::quote! {},
+            needs_alloc: false,
+        }
+    }
+
+    fn enabled(
+        fsm: &fsm::UmlFsm,
+        idents: &ident::Idents,
+        deferred: &DeferredEventsCodegen,
+        posted: &PostedEventsCodegen,
+        hash: u64,
+    ) -> Self {
+        let fsm_ident = &idents.fsm;
+        let fsm_inner = &idents.fsm_inner;
+        let actions_trait = &idents.action_trait;
+        let observer_trait = &idents.observer_trait;
+        let state_struct = &idents.state_struct;
+        let state_id_enum = &idents.state_id_enum;
+        let init_state_id_variant = &idents.init_state_id_variant;
+        let event_enum = &idents.event_enum;
+        let snapshot = &idents.snapshot_struct;
+        let restore_error = &idents.restore_error;
+
+        let from_id_arms = fsm.states().map(|state| {
+            let variant = state.state_id_variant_ident();
+            let fn_ident = state.function_ident();
+            quote::quote! { #state_id_enum::#variant => Self::#fn_ident(), }
+        });
+        let state_functions = quote::quote! {
+            fn from_id(id: #state_id_enum) -> Self {
+                match id {
+                    #(#from_id_arms)*
+                    #state_id_enum::#init_state_id_variant => Self::init(),
+                }
+            }
+        };
+
+        let with_deferred = deferred.is_enabled();
+        let (snapshot_generics, snapshot_struct, deferred_snapshot, deferred_restore, mutability) =
+            if with_deferred {
+                let serialize_bound = bound(quote::quote! {
+                    #event_enum<A>: ::serde::Serialize
+                });
+                let deserialize_bound = bound(quote::quote! {
+                    #event_enum<A>: ::serde::Deserialize<'de>
+                });
+                (
+                    quote::quote! { <A> },
+                    quote::quote! {
+                        #[derive(::serde::Serialize, ::serde::Deserialize)]
+                        #[serde(bound(serialize = #serialize_bound, deserialize = #deserialize_bound))]
+                        pub struct #snapshot<A: #actions_trait> {
+                            diagram_hash: u64,
+                            state: #state_id_enum,
+                            deferred_events: alloc::vec::Vec<#event_enum<A>>,
+                        }
+                    },
+                    quote::quote! {
+                        deferred_events: self.0.deferred_events.iter().cloned().collect(),
+                    },
+                    quote::quote! {
+                        for event in snapshot.deferred_events {
+                            fsm.deferred_events
+                                .push_back(event)
+                                .map_err(|_| #restore_error::DeferredQueueFull)?;
+                        }
+                    },
+                    quote::quote! { mut },
+                )
+            } else {
+                (
+                    quote::quote! {},
+                    quote::quote! {
+                        #[derive(::serde::Serialize, ::serde::Deserialize)]
+                        pub struct #snapshot {
+                            diagram_hash: u64,
+                            state: #state_id_enum,
+                        }
+                    },
+                    quote::quote! {},
+                    quote::quote! {},
+                    quote::quote! {},
+                )
+            };
+        let snapshot_bound = if with_deferred {
+            quote::quote! { where #event_enum<A>: Clone }
+        } else {
+            quote::quote! {}
+        };
+        let impl_generics = if with_deferred {
+            quote::quote! { <A: #actions_trait> }
+        } else {
+            quote::quote! {}
+        };
+        let deferred_init = &deferred.fsm_init_field;
+        let posted_init = &posted.fsm_init_field;
+        let queue_iter = deferred.queue_iter(idents);
+
+        let event_attributes = if with_deferred {
+            let params = extract::events(fsm)
+                .map(|event| event.params_ident())
+                .collect::<Vec<_>>();
+            let serialize_bound = bound(quote::quote! {
+                #(P::#params: ::serde::Serialize,)*
+            });
+            let deserialize_bound = bound(quote::quote! {
+                #(P::#params: ::serde::Deserialize<'de>,)*
+            });
+            quote::quote! {
+                #[derive(::serde::Serialize, ::serde::Deserialize)]
+                #[serde(bound(serialize = #serialize_bound, deserialize = #deserialize_bound))]
+            }
+        } else {
+            quote::quote! {}
+        };
+
+        Self {
+            state_id_derive: quote::quote! {
+                #[derive(::serde::Serialize, ::serde::Deserialize)]
+            },
+            event_attributes,
+            state_functions,
+            fsm_methods: quote::quote! {
+                /// The current state and the pending deferred events, to `restore()` the FSM later
+                pub fn snapshot(&self) -> #snapshot #snapshot_generics #snapshot_bound {
+                    #snapshot {
+                        diagram_hash: DIAGRAM_HASH,
+                        state: self.0.current_state.id,
+                        #deferred_snapshot
+                    }
+                }
+            },
+            items: quote::quote! {
+                /// Identifies the diagram, snapshots of other diagrams or versions of it are rejected
+                pub const DIAGRAM_HASH: u64 = #hash;
+
+                /// The state of an FSM, serialized to resume it later on
+                #snapshot_struct
+
+                impl #impl_generics #snapshot #snapshot_generics {
+                    /// The current state when the snapshot was taken
+                    pub fn state(&self) -> #state_id_enum {
+                        self.state
+                    }
+                }
+
+                #[derive(Copy, Clone, PartialEq, Eq, Debug)]
+                pub enum #restore_error {
+                    /// The snapshot was taken from another diagram or version of it
+                    DiagramMismatch { expected: u64, found: u64 },
+                    /// The snapshot holds more deferred events than the queue has capacity for
+                    DeferredQueueFull,
+                    /// The snapshot holds the pseudo-state before the FSM was started
+                    NotStarted,
+                }
+
+                impl ::core::fmt::Display for #restore_error {
+                    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                        match self {
+                            Self::DiagramMismatch { expected, found } => write!(
+                                f,
+                                "snapshot of diagram {found:#018x} cannot be restored by diagram {expected:#018x}"
+                            ),
+                            Self::DeferredQueueFull => write!(f, "too many deferred events in snapshot"),
+                            Self::NotStarted => write!(f, "snapshot of an FSM which was not started"),
+                        }
+                    }
+                }
+
+                impl ::core::error::Error for #restore_error {}
+
+                #queue_iter
+
+                /// Resumes the FSM in the state of the snapshot, without running any enter actions
+                pub fn restore<A: #actions_trait>(
+                    snapshot: #snapshot #snapshot_generics,
+                    actions: A,
+                ) -> Result<#fsm_ident<A>, #restore_error> {
+                    restore_with_observer(snapshot, actions, ())
+                }
+
+                /// Resumes the FSM like `restore()`, reporting its further steps to the observer
+                pub fn restore_with_observer<A: #actions_trait, O: #observer_trait>(
+                    snapshot: #snapshot #snapshot_generics,
+                    actions: A,
+                    observer: O,
+                ) -> Result<#fsm_ident<A, O>, #restore_error> {
+                    if snapshot.diagram_hash != DIAGRAM_HASH {
+                        return Err(#restore_error::DiagramMismatch {
+                            expected: DIAGRAM_HASH,
+                            found: snapshot.diagram_hash,
+                        });
+                    }
+                    if snapshot.state == #state_id_enum::#init_state_id_variant {
+                        return Err(#restore_error::NotStarted);
+                    }
+                    let #mutability fsm = #fsm_inner {
+                        actions,
+                        observer,
+                        current_state: #state_struct::from_id(snapshot.state),
+                        #deferred_init
+                        #posted_init
+                    };
+                    #deferred_restore
+                    Ok(#fsm_ident(fsm))
+                }
+            },
+            needs_alloc: with_deferred,
+        }
+    }
+}
+
+/// A `where` clause of serde's `bound` attribute
+fn bound(predicates: proc_macro2::TokenStream) -> proc_macro2::Literal {
+    proc_macro2::Literal::string(&predicates.to_string())
+}
+
+/// FNV-1a of the states and transitions, stable across builds and compiler versions
+pub fn diagram_hash(fsm: &fsm::UmlFsm) -> u64 {
+    let qualified = |state: &fsm::State<'_>| state.qualified_name("::");
+    let mut lines = vec![format!("fsm {}", fsm.name())];
+    for state in fsm.states() {
+        lines.push(format!("state {}", qualified(&state)));
+        lines.extend(state.enter_action().map(|a| format!("entry {a}")));
+        lines.extend(state.exit_action().map(|a| format!("exit {a}")));
+        lines.push(format!("enters {}", qualified(&state.enter_state())));
+        lines.extend(state.deferred_events().map(|e| format!("defers {e}")));
+    }
+    for t in fsm.transitions() {
+        lines.push(format!(
+            "transition {} -[{}|{}|{}]-> {}",
+            qualified(&t.source),
+            t.event.map(ToString::to_string).unwrap_or_default(),
+            t.guard.map(ToString::to_string).unwrap_or_default(),
+            t.action.map(ToString::to_string).unwrap_or_default(),
+            t.destination.as_ref().map(qualified).unwrap_or_default(),
+        ));
+    }
+
+    lines
+        .join("\n")
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::diagram_hash;
+    use crate::fsm::{StateType, TransitionParameters, UmlFsm, UmlFsmBuilder};
+
+    fn fsm_with_transition(event: &str) -> UmlFsm {
+        let mut builder = UmlFsmBuilder::new("Test");
+        builder.add_state("A", StateType::Enter);
+        builder.add_transition(TransitionParameters {
+            source: "A",
+            target: Some("B"),
+            event: Some(event.into()),
+            action: None,
+            guard: None,
+        });
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn diagram_hash_changes_with_the_diagram() {
+        let hash = diagram_hash(&fsm_with_transition("Go"));
+        assert_eq!(hash, diagram_hash(&fsm_with_transition("Go")));
+        assert_ne!(hash, diagram_hash(&fsm_with_transition("Stay")));
+    }
+}
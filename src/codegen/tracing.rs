@@ -0,0 +1,73 @@
+use super::asyncness::AsyncActionsCodegen;
+
+pub struct TracingCodegen {
+    level: Option<proc_macro2::TokenStream>,
+    fsm_name: String,
+    is_async: bool,
+}
+
+impl TracingCodegen {
+    pub fn new(fsm_name: &str, level: Option<log::Level>, asyncness: &AsyncActionsCodegen) -> Self {
+        Self {
+            level: level.map(level_token),
+            fsm_name: fsm_name.to_string(),
+            is_async: asyncness.is_enabled(),
+        }
+    }
+
+    /// Runs the body, which dispatches `event`, in a span named `name`. The span records the
+    /// state before and after the dispatch, and is the parent of the spans opened by the body.
+    pub fn instrument(
+        &self,
+        name: &str,
+        body: proc_macro2::TokenStream,
+    ) -> proc_macro2::TokenStream {
+        let Some(level) = &self.level else {
+            return body;
+        };
+        let fsm_name = &self.fsm_name;
+        let traced = if self.is_async {
+            quote::quote! { ::tracing::Instrument::instrument(async { #body }, span.clone()).await }
+        } else {
+            quote::quote! {{
+                let _entered = span.enter();
+                #body
+            }}
+        };
+        quote::quote! {
+            let span = ::tracing::span!(
+                #level,
+                #name,
+                fsm = #fsm_name,
+                event = %event.kind(),
+                state = %self.current_state.id,
+                final_state = ::tracing::field::Empty,
+            );
+            let result = #traced;
+            span.record("final_state", ::tracing::field::display(self.current_state.id));
+            result
+        }
+    }
+
+    /// A step within the span of the dispatch
+    pub fn event(
+        &self,
+        fields: proc_macro2::TokenStream,
+        message: &str,
+    ) -> proc_macro2::TokenStream {
+        match &self.level {
+            Some(level) => quote::quote! { ::tracing::event!(#level, #fields, #message); },
+            None => quote::quote! {},
+        }
+    }
+}
+
+fn level_token(level: log::Level) -> proc_macro2::TokenStream {
+    match level {
+        log::Level::Error => quote::quote! { ::tracing::Level::ERROR },
+        log::Level::Warn => quote::quote! { ::tracing::Level::WARN },
+        log::Level::Info => quote::quote! { ::tracing::Level::INFO },
+        log::Level::Debug => quote::quote! { ::tracing::Level::DEBUG },
+        log::Level::Trace => quote::quote! { ::tracing::Level::TRACE },
+    }
+}
@@ -1,5 +1,12 @@
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// Position within the diagram source, 1-based
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
 #[derive(PartialEq, Eq, Debug, thiserror::Error)]
 pub enum Error {
     #[error("Invalid macro input: {0}")]
@@ -8,4 +15,28 @@ pub enum Error {
     InvalidFile(String, String),
     #[error("Parse error: {0}")]
     Parse(String),
+    /// A parse error at a known position of the source
+    #[error("Parse error: {0}")]
+    Syntax(String, Position),
+    #[error("Name mapping error: {0}")]
+    NameMapping(String),
+}
+
+impl Error {
+    pub fn position(&self) -> Option<Position> {
+        match self {
+            Error::Syntax(_, position) => Some(*position),
+            _ => None,
+        }
+    }
+}
+
+impl<R: pest::RuleType> From<pest::error::Error<R>> for Error {
+    fn from(error: pest::error::Error<R>) -> Self {
+        let (line, column) = match error.line_col {
+            pest::error::LineColLocation::Pos(pos) => pos,
+            pest::error::LineColLocation::Span(start, _) => start,
+        };
+        Error::Syntax(error.to_string(), Position { line, column })
+    }
 }
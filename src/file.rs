@@ -1,7 +1,15 @@
-use crate::error;
+use std::path::{Path, PathBuf};
+
+use itertools::Itertools;
+
+use crate::{
+    error,
+    markdown::{self, Document},
+    parser::Format,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct FilePath(std::path::PathBuf);
+pub struct FilePath(PathBuf);
 
 impl std::fmt::Display for FilePath {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -10,66 +18,211 @@ impl std::fmt::Display for FilePath {
 }
 
 impl FilePath {
-    /// Creates a FilePath, resolving relative paths in the following order:
+    /// Creates a FilePath. `$VAR` and `${VAR}` are expanded with the environment of the build,
+    /// e.g. `$OUT_DIR` for diagrams generated by a build script. Then the path is resolved in the
+    /// following order:
     /// 1. Absolute paths are used as-is
-    /// 2. Paths starting with `../` are resolved relative to the caller's location
-    /// 3. Other relative paths are resolved relative to `src/` in the manifest directory
-    pub fn resolve(file_path: &str, span: proc_macro::Span) -> Self {
+    /// 2. Paths starting with `workspace:` are resolved relative to the workspace root
+    /// 3. Paths starting with `./` or `../` are resolved relative to the caller's location
+    /// 4. Other relative paths are resolved relative to `src/` in the manifest directory, or else
+    ///    to the manifest directory itself
+    ///
+    /// The first existing candidate is used, otherwise the error lists all candidates.
+    pub fn resolve(file_path: &str, span: proc_macro::Span) -> error::Result<Self> {
         let file_path_str = file_path.trim_matches('"');
-        let file_path = std::path::PathBuf::from(file_path_str);
+        let expanded = expand_env_vars(file_path_str, |name| std::env::var(name).ok())?;
+        let candidates = FilePath::candidates(&expanded, span);
 
-        if file_path.is_absolute() {
-            return Self(file_path);
-        }
+        candidates
+            .iter()
+            .find(|candidate| candidate.exists())
+            .map(|candidate| Self(candidate.clone()))
+            .ok_or_else(|| {
+                let tried = candidates.iter().map(|c| c.display()).join(", ");
+                error::Error::InvalidFile(
+                    file_path_str.to_string(),
+                    format!("No such file, tried: {tried}"),
+                )
+            })
+    }
+
+    pub fn extension(&self) -> Option<&str> {
+        self.0.extension().and_then(|e| e.to_str())
+    }
 
-        if is_relative_path(&file_path) {
-            return FilePath::resolve_relative_to_caller(file_path_str, span);
+    /// The files of this directory matching the glob, sorted by path
+    pub fn find_files(&self, glob: &str) -> error::Result<Vec<FilePath>> {
+        if !self.0.is_dir() {
+            return Err(error::Error::InvalidFile(
+                self.to_string(),
+                "Not a directory".to_string(),
+            ));
         }
+        let pattern = format!(
+            "{}/{glob}",
+            glob::Pattern::escape(&self.0.to_string_lossy())
+        );
+        let paths = glob::glob(&pattern)
+            .map_err(|e| error::Error::InvalidInput(format!("Invalid glob '{glob}': {e}")))?;
 
-        FilePath::resolve_to_src_dir(file_path_str)
+        paths
+            .filter_ok(|path| path.is_file())
+            .map_ok(FilePath)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| error::Error::InvalidFile(e.path().display().to_string(), e.to_string()))
     }
 
-    fn resolve_relative_to_caller(file_path: &str, span: proc_macro::Span) -> Self {
-        let caller_file = span.local_file().unwrap_or_default();
-        let caller_dir = caller_file.parent().unwrap_or(std::path::Path::new("."));
-        Self(caller_dir.join(file_path))
-    }
+    fn candidates(file_path: &str, span: proc_macro::Span) -> Vec<PathBuf> {
+        let manifest_dir =
+            PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap_or_else(|_| ".".to_string()));
+        if let Some(file_path) = file_path.strip_prefix("workspace:") {
+            return vec![workspace_root(&manifest_dir).join(file_path)];
+        }
 
-    fn resolve_to_src_dir(file_path: &str) -> Self {
-        let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_else(|_| ".".to_string());
-        let src_path = std::path::PathBuf::from(manifest_dir)
-            .join("src")
-            .join(file_path);
-        Self(src_path)
+        let file_path = Path::new(file_path);
+        if file_path.is_absolute() {
+            vec![file_path.to_path_buf()]
+        } else if is_relative_path(file_path) {
+            let caller_file = span.local_file().unwrap_or_default();
+            let caller_dir = caller_file.parent().unwrap_or(Path::new("."));
+            vec![caller_dir.join(file_path)]
+        } else {
+            vec![
+                manifest_dir.join("src").join(file_path),
+                manifest_dir.join(file_path),
+            ]
+        }
     }
 }
 
-fn is_relative_path(file_path: &std::path::Path) -> bool {
+fn is_relative_path(file_path: &Path) -> bool {
     file_path.starts_with("../") || file_path.starts_with("./")
 }
 
+/// Replaces `$VAR` and `${VAR}` by the value of the variable
+fn expand_env_vars(
+    file_path: &str,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> error::Result<String> {
+    let mut expanded = String::new();
+    let mut rest = file_path;
+    while let Some(start) = rest.find('$') {
+        expanded.push_str(&rest[..start]);
+        let variable = &rest[start + 1..];
+        let (name, remainder) = match variable.strip_prefix('{') {
+            Some(braced) => {
+                let end = braced.find('}').ok_or_else(|| {
+                    error::Error::InvalidInput(format!("Unclosed '${{' in file path '{file_path}'"))
+                })?;
+                (&braced[..end], &braced[end + 1..])
+            }
+            None => {
+                let end = variable
+                    .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                    .unwrap_or(variable.len());
+                variable.split_at(end)
+            }
+        };
+        if name.is_empty() {
+            return Err(error::Error::InvalidInput(format!(
+                "Missing variable name after '$' in file path '{file_path}'"
+            )));
+        }
+        let value = lookup(name).ok_or_else(|| {
+            error::Error::InvalidInput(format!(
+                "Environment variable '{name}' of file path '{file_path}' is not set"
+            ))
+        })?;
+        expanded.push_str(&value);
+        rest = remainder;
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+/// The closest directory with a `[workspace]` manifest, or the manifest directory itself if the
+/// package is not part of a workspace
+fn workspace_root(manifest_dir: &Path) -> PathBuf {
+    manifest_dir
+        .ancestors()
+        .find(|dir| {
+            std::fs::read_to_string(dir.join("Cargo.toml"))
+                .is_ok_and(|manifest| manifest.lines().any(|line| line.trim() == "[workspace]"))
+        })
+        .unwrap_or(manifest_dir)
+        .to_path_buf()
+}
+
 pub struct FsmFile {
+    path: FilePath,
     content: String,
+    format: Format,
 }
 
 impl FsmFile {
-    pub fn try_open(file_path: FilePath) -> error::Result<Self> {
+    /// Opens the file, its format is detected by the extension unless given.
+    ///
+    /// Of Markdown and Rust files, the diagram is extracted from the ```` ```plantuml ```` or
+    /// ```` ```mermaid ```` fences, optionally selected by the name following the language.
+    pub fn try_open(
+        file_path: FilePath,
+        format: Option<Format>,
+        fence: Option<&str>,
+    ) -> error::Result<Self> {
         let error =
             |e: std::io::Error| error::Error::InvalidFile(file_path.to_string(), e.to_string());
         let content = std::fs::read_to_string(&file_path.0).map_err(error)?;
 
-        Ok(Self { content })
+        let (content, format) = match Document::from_extension(file_path.extension()) {
+            Some(document) => markdown::extract(&content, document, format, fence)
+                .map_err(|e| error::Error::InvalidFile(file_path.to_string(), e.to_string()))?,
+            None if fence.is_some() => {
+                return Err(error::Error::InvalidInput(
+                    "The 'fence' option requires a Markdown (.md) or Rust (.rs) file".to_string(),
+                ));
+            }
+            None => {
+                let format =
+                    format.unwrap_or_else(|| Format::from_extension(file_path.extension()));
+                (content, format)
+            }
+        };
+
+        Ok(Self {
+            path: file_path,
+            content,
+            format,
+        })
+    }
+
+    /// Includes the file in the generated code, so that cargo rebuilds the crate when it changes
+    pub fn dependency(&self) -> proc_macro2::TokenStream {
+        let path = std::fs::canonicalize(&self.path.0).unwrap_or_else(|_| self.path.0.clone());
+        let path = path.to_string_lossy();
+        quote::quote! {
+            const _: &[u8] = include_bytes!(#path);
+        }
+    }
+
+    pub fn path(&self) -> &FilePath {
+        &self.path
     }
 
     pub fn content(&self) -> &str {
         &self.content
     }
+
+    pub fn format(&self) -> Format {
+        self.format
+    }
 }
 
 #[cfg(test)]
 mod test {
+    use std::path::Path;
+
     use crate::{
-        file::{FilePath, FsmFile},
+        file::{FilePath, FsmFile, expand_env_vars, workspace_root},
         test,
     };
 
@@ -92,10 +245,85 @@ mod test {
 
         // TODO use the actual method
         let file_path = FilePath(test_data.path);
-        let fsm_file = FsmFile::try_open(file_path).expect("Failed to open FSM file");
+        let fsm_file = FsmFile::try_open(file_path, None, None).expect("Failed to open FSM file");
         assert!(
             !fsm_file.content.is_empty(),
             "FSM file content should not be empty"
         );
     }
+
+    #[test]
+    fn dependency_includes_absolute_path() {
+        let test_data = test::FsmTestData::four_seasons();
+        let expected = std::fs::canonicalize(&test_data.path).unwrap();
+
+        let fsm_file = FsmFile::try_open(FilePath(test_data.path), None, None).unwrap();
+        let dependency = fsm_file.dependency().to_string();
+        assert!(dependency.contains("include_bytes"), "{dependency}");
+        assert!(
+            dependency.contains(&format!("{:?}", expected.to_string_lossy())),
+            "{dependency}"
+        );
+    }
+
+    #[test]
+    fn find_files_in_dir() {
+        let dir = FilePath(Path::new(env!("CARGO_MANIFEST_DIR")).join("src/test"));
+        let file_names = |glob| {
+            dir.find_files(glob)
+                .unwrap()
+                .iter()
+                .map(|file| file.0.file_name().unwrap().to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(file_names("misc/*.puml")[0], "internal_names.puml");
+        assert_eq!(file_names("*/*.mmd"), vec!["door.mmd"]);
+        assert!(file_names("*.puml").is_empty());
+        assert!(file_names("**/*.puml").contains(&"four_seasons.puml".to_string()));
+        assert!(FilePath(dir.0.join("mod.rs")).find_files("*").is_err());
+    }
+
+    #[test]
+    fn expand_env_vars_in_path() {
+        let lookup = |name: &str| match name {
+            "OUT_DIR" => Some("/target/out".to_string()),
+            "FSM_DIR" => Some("fsm".to_string()),
+            _ => None,
+        };
+        let expand = |path| expand_env_vars(path, lookup);
+
+        assert_eq!(
+            expand("$OUT_DIR/door.puml").unwrap(),
+            "/target/out/door.puml"
+        );
+        assert_eq!(
+            expand("${FSM_DIR}_v2/door.puml").unwrap(),
+            "fsm_v2/door.puml"
+        );
+        assert_eq!(expand("$FSM_DIR/${FSM_DIR}").unwrap(), "fsm/fsm");
+        assert_eq!(expand("door.puml").unwrap(), "door.puml");
+        assert!(expand("$UNKNOWN/door.puml").is_err());
+        assert!(expand("${OUT_DIR/door.puml").is_err());
+        assert!(expand("$/door.puml").is_err());
+    }
+
+    #[test]
+    fn workspace_root_of_member() {
+        let root = std::env::temp_dir().join(format!("phyto-fsm-workspace-{}", std::process::id()));
+        let member = root.join("crates").join("member");
+        std::fs::create_dir_all(&member).unwrap();
+        std::fs::write(
+            root.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"crates/*\"]\n",
+        )
+        .unwrap();
+        std::fs::write(member.join("Cargo.toml"), "[package]\nname = \"member\"\n").unwrap();
+
+        assert_eq!(workspace_root(&member), root);
+        std::fs::remove_dir_all(&root).unwrap();
+
+        let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+        assert_eq!(workspace_root(manifest_dir), manifest_dir);
+    }
 }
@@ -1,4 +1,6 @@
-use itertools::Itertools;
+use std::collections::HashMap;
+
+use itertools::{Either, Itertools};
 use log::{debug, trace};
 
 use crate::error::{Error, Result};
@@ -7,8 +9,10 @@ use super::model::{StateData, StateId, TransitionData, TransitionParameters, Uml
 use super::types::{Action, Event, StateType};
 
 mod inheritance;
+mod resolution;
 mod scoped_arena;
 mod validation;
+use resolution::{Reference, StateRef};
 use scoped_arena::ScopedArena;
 
 #[cfg(test)]
@@ -32,6 +36,10 @@ impl StateData {
 pub struct UmlFsmBuilder {
     name: String,
     arena: ScopedArena<StateData>,
+    /// Resolved on build, once all states are declared
+    references: Vec<Reference>,
+    /// Enter states given by a qualified name, by the scope they are the enter state of
+    qualified_enter_states: HashMap<Option<StateId>, StateId>,
 }
 
 impl UmlFsmBuilder {
@@ -39,6 +47,8 @@ impl UmlFsmBuilder {
         Self {
             name: name.into(),
             arena: ScopedArena::new(),
+            references: vec![],
+            qualified_enter_states: HashMap::new(),
         }
     }
 
@@ -46,9 +56,36 @@ impl UmlFsmBuilder {
         self.arena.set_scope(scope)
     }
 
+    /// Declares a state in the current scope. A qualified name, e.g. `Parent.Child`, declares
+    /// each of the nested states, all with the given type.
     pub fn add_state(&mut self, name: &str, state_type: StateType) -> StateId {
         debug!("Adding state '{}' of type {:?}", name, state_type);
 
+        let previous_scope = self.arena.scope();
+        let mut segments = resolution::segments(name);
+        let mut id = self.declare_state(segments.next().unwrap_or_default(), state_type);
+        for segment in segments {
+            self.arena.set_scope(Some(id));
+            id = self.declare_state(segment, state_type);
+        }
+        self.arena.set_scope(previous_scope);
+        id
+    }
+
+    /// Declares the enter state of the current scope. A qualified name, e.g. `Parent.Child`, is
+    /// resolved like the target of a transition once all states are declared. The nested state is
+    /// entered through its parents, without changing their own enter states.
+    pub fn add_enter_state(&mut self, name: &str) {
+        if !resolution::is_qualified(name) {
+            self.add_state(name, StateType::Enter);
+            return;
+        }
+        debug!("Adding qualified enter state '{}'", name);
+        let reference = Reference::EnterState(self.state_ref(name));
+        self.references.push(reference);
+    }
+
+    fn declare_state(&mut self, name: &str, state_type: StateType) -> StateId {
         if let Some(id) = self.find_state_in_scope(name) {
             self.update_non_simple_state_type(id, state_type, name);
             return id;
@@ -71,32 +108,26 @@ impl UmlFsmBuilder {
             source, target, event, guard, action
         );
 
-        let from_id = self.find_or_create_state(source);
-        let to_id = target.map(|t| self.find_or_create_state(t));
-
-        let transition = TransitionData {
-            source: from_id,
-            target: to_id,
+        let reference = Reference::Transition {
+            source: self.state_ref(source),
+            target: target.map(|t| self.state_ref(t)),
             event,
             action,
             guard,
         };
-
-        self.arena[from_id].get_mut().transitions.push(transition);
+        self.references.push(reference);
     }
 
     pub fn add_enter_action(&mut self, state_name: &str, action: Action) {
         debug!("Adding enter action '{}' to state '{}'", action, state_name);
-        if let Some(id) = self.find_descendant_state(state_name) {
-            self.arena[id].get_mut().enter_action = Some(action);
-        }
+        let reference = Reference::EnterAction(self.state_ref(state_name), action);
+        self.references.push(reference);
     }
 
     pub fn add_exit_action(&mut self, state_name: &str, action: Action) {
         debug!("Adding exit action '{}' to state '{}'", action, state_name);
-        if let Some(id) = self.find_descendant_state(state_name) {
-            self.arena[id].get_mut().exit_action = Some(action);
-        }
+        let reference = Reference::ExitAction(self.state_ref(state_name), action);
+        self.references.push(reference);
     }
 
     pub fn add_deferred_event(&mut self, state_name: &str, event: Event) {
@@ -104,9 +135,8 @@ impl UmlFsmBuilder {
             "Adding deferred event '{}' to state '{}'",
             event, state_name
         );
-        if let Some(id) = self.find_descendant_state(state_name) {
-            self.arena[id].get_mut().deferred_events.push(event);
-        }
+        let reference = Reference::DeferredEvent(self.state_ref(state_name), event);
+        self.references.push(reference);
     }
 
     pub fn build(mut self) -> Result<UmlFsm> {
@@ -118,6 +148,8 @@ impl UmlFsmBuilder {
                 .collect::<Vec<_>>()
         );
 
+        self.resolve_references()?;
+
         validation::injective_action_mapping(&self.arena)?;
         validation::no_conflicting_transitions(&self.arena)?;
         validation::unique_guards_per_event(&self.arena)?;
@@ -136,9 +168,85 @@ impl UmlFsmBuilder {
         Ok(UmlFsm::new(name, enter_state, self.arena.into_inner()))
     }
 
-    fn find_or_create_state(&mut self, name: &str) -> StateId {
-        self.find_descendant_state(name)
-            .unwrap_or_else(|| self.create_state(name, StateType::Simple))
+    fn state_ref(&self, name: &str) -> StateRef {
+        StateRef {
+            scope: self.arena.scope(),
+            name: name.to_string(),
+        }
+    }
+
+    fn resolve_references(&mut self) -> Result<()> {
+        let references = std::mem::take(&mut self.references);
+        resolution::declare_implicit_states(&mut self.arena, &references);
+
+        for reference in references {
+            match reference {
+                Reference::Transition {
+                    source,
+                    target,
+                    event,
+                    action,
+                    guard,
+                } => {
+                    let from_id = resolution::resolve(&self.arena, &source)?;
+                    let to_id = target
+                        .map(|t| resolution::resolve(&self.arena, &t))
+                        .transpose()?;
+                    let transition = TransitionData {
+                        source: from_id,
+                        target: to_id,
+                        event,
+                        action,
+                        guard,
+                    };
+                    self.arena[from_id].get_mut().transitions.push(transition);
+                }
+                Reference::EnterAction(state, action) => {
+                    let id = resolution::resolve(&self.arena, &state)?;
+                    self.arena[id].get_mut().enter_action = Some(action);
+                }
+                Reference::ExitAction(state, action) => {
+                    let id = resolution::resolve(&self.arena, &state)?;
+                    self.arena[id].get_mut().exit_action = Some(action);
+                }
+                Reference::DeferredEvent(state, event) => {
+                    let id = resolution::resolve(&self.arena, &state)?;
+                    self.arena[id].get_mut().deferred_events.push(event);
+                }
+                Reference::EnterState(state) => {
+                    let id = resolution::resolve(&self.arena, &state)?;
+                    self.add_qualified_enter_state(state.scope, id)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn add_qualified_enter_state(&mut self, scope: Option<StateId>, id: StateId) -> Result<()> {
+        let scope_name = scope.map_or(self.name.as_str(), |scope| &self.arena[scope].get().name);
+        let state_name = &self.arena[id].get().name;
+        if let Some(scope) = scope
+            && !self.arena.ancestors(id).skip(1).contains(&scope)
+        {
+            return Err(Error::Parse(format!(
+                "Enter state '{state_name}' of '{scope_name}' must be one of its substates"
+            )));
+        }
+
+        let mut scope_states = match scope {
+            Some(scope) => Either::Left(self.arena.children(scope)),
+            None => Either::Right(self.arena.root_node_ids()),
+        };
+        let has_enter_state =
+            scope_states.any(|child| self.arena[child].get().state_type == StateType::Enter);
+        if has_enter_state || self.qualified_enter_states.contains_key(&scope) {
+            return Err(Error::Parse(format!(
+                "'{scope_name}' must have exactly one enter state, found another one besides '{state_name}'"
+            )));
+        }
+
+        self.qualified_enter_states.insert(scope, id);
+        Ok(())
     }
 
     fn create_state(&mut self, name: &str, state_type: StateType) -> StateId {
@@ -165,6 +273,10 @@ impl UmlFsmBuilder {
     }
 
     fn find_root_enter_state(&self) -> Result<StateId> {
+        if let Some(&enter_state) = self.qualified_enter_states.get(&None) {
+            return Ok(self.find_deepest_enter_state(enter_state));
+        }
+
         let enter_states = self
             .arena
             .root_nodes()
@@ -190,9 +302,14 @@ impl UmlFsmBuilder {
     fn find_deepest_enter_state(&self, state_id: StateId) -> StateId {
         let mut current = state_id;
         while let Some(nested_enter) = self
-            .arena
-            .children(current)
-            .find(|child| self.arena[*child].get().state_type == StateType::Enter)
+            .qualified_enter_states
+            .get(&Some(current))
+            .copied()
+            .or_else(|| {
+                self.arena
+                    .children(current)
+                    .find(|child| self.arena[*child].get().state_type == StateType::Enter)
+            })
         {
             current = nested_enter;
         }
@@ -206,13 +323,6 @@ impl UmlFsmBuilder {
             .and_then(|node| self.arena.get_node_id(node))
     }
 
-    fn find_descendant_state(&self, name: &str) -> Option<StateId> {
-        self.arena
-            .descendants_from_scope()
-            .find(|node| node.get().name == name)
-            .and_then(|node| self.arena.get_node_id(node))
-    }
-
     fn update_non_simple_state_type(&mut self, id: StateId, state_type: StateType, name: &str) {
         let current_type = self.arena[id].get().state_type;
         if state_type != StateType::Simple && current_type == StateType::Simple {
@@ -0,0 +1,144 @@
+use std::collections::HashSet;
+
+use indextree::NodeId;
+use itertools::{Either, Itertools};
+
+use super::scoped_arena::ScopedArena;
+use crate::error::{Error, Result};
+use crate::fsm::model::{State, StateData};
+use crate::fsm::types::{Action, Event, StateType};
+
+/// A state referenced by name from within a scope. The name may be qualified by the names of its
+/// parents, e.g. `Parent.Child` or `Parent::Child`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StateRef {
+    pub scope: Option<NodeId>,
+    pub name: String,
+}
+
+/// An element referring to states by name, which is resolved once all states are declared.
+#[derive(Debug, Clone)]
+pub enum Reference {
+    Transition {
+        source: StateRef,
+        target: Option<StateRef>,
+        event: Option<Event>,
+        action: Option<Action>,
+        guard: Option<Action>,
+    },
+    EnterAction(StateRef, Action),
+    ExitAction(StateRef, Action),
+    DeferredEvent(StateRef, Event),
+    /// The enter state of the scope, given by a qualified name
+    EnterState(StateRef),
+}
+
+impl Reference {
+    fn state_refs(&self) -> impl Iterator<Item = &StateRef> {
+        match self {
+            Reference::Transition { source, target, .. } => {
+                Either::Left(std::iter::once(source).chain(target.iter()))
+            }
+            Reference::EnterAction(state, _)
+            | Reference::ExitAction(state, _)
+            | Reference::DeferredEvent(state, _)
+            | Reference::EnterState(state) => Either::Right(std::iter::once(state)),
+        }
+    }
+}
+
+/// Creates the states which are only referenced, but never declared.
+///
+/// Such a state is created in the scope of its first reference. Later references create another
+/// state, unless one is already visible from their scope, i.e. it is a descendant of the scope or
+/// a child of an enclosing scope.
+pub fn declare_implicit_states(arena: &mut ScopedArena<StateData>, references: &[Reference]) {
+    let declared: HashSet<_> = arena.iter().map(|node| node.get().name.clone()).collect();
+    let implicit = references
+        .iter()
+        .flat_map(Reference::state_refs)
+        .filter(|state| !is_qualified(&state.name) && !declared.contains(&state.name));
+
+    let previous_scope = arena.scope();
+    for state in implicit {
+        if is_visible(arena, state) {
+            continue;
+        }
+        log::debug!(
+            "Creating implicitly declared state '{}' in scope {:?}",
+            state.name,
+            state.scope
+        );
+        arena.set_scope(state.scope);
+        arena.new_node_in_scope(StateData::new(&state.name, StateType::Simple));
+    }
+    arena.set_scope(previous_scope);
+}
+
+/// Splits a possibly qualified state name into the names of the state and its parents.
+pub fn segments(name: &str) -> impl Iterator<Item = &str> {
+    name.split("::").flat_map(|segment| segment.split('.'))
+}
+
+pub fn is_qualified(name: &str) -> bool {
+    segments(name).nth(1).is_some()
+}
+
+/// Resolves a state reference, searching the descendants of its scope first and widening the
+/// search to the enclosing scopes until a match is found. A qualified reference matches the
+/// states whose qualified name ends with it. Within a scope, the states nested the least are
+/// preferred, only several of them at the same depth are ambiguous.
+pub fn resolve(arena: &ScopedArena<StateData>, state: &StateRef) -> Result<NodeId> {
+    let segments = segments(&state.name).collect_vec();
+    let enclosing_scopes = state
+        .scope
+        .into_iter()
+        .flat_map(|scope| arena.ancestors(scope).map(Some))
+        .chain(std::iter::once(None));
+
+    for scope in enclosing_scopes {
+        let candidates = descendants(arena, scope)
+            .filter(|id| State::new(*id, arena.inner()).path().ends_with(&segments))
+            .min_set_by_key(|id| arena.ancestors(*id).count());
+        match candidates.as_slice() {
+            [] => continue,
+            [id] => return Ok(*id),
+            _ => {
+                let names = candidates
+                    .iter()
+                    .map(|id| State::new(*id, arena.inner()).qualified_name("::"))
+                    .join(", ");
+                return Err(Error::Parse(format!(
+                    "State '{}' is ambiguous, candidates are: {names}",
+                    state.name
+                )));
+            }
+        }
+    }
+
+    Err(Error::Parse(format!("Unknown state '{}'", state.name)))
+}
+
+fn descendants(
+    arena: &ScopedArena<StateData>,
+    scope: Option<NodeId>,
+) -> impl Iterator<Item = NodeId> + '_ {
+    let scopes = match scope {
+        Some(id) => Either::Left(std::iter::once(id)),
+        None => Either::Right(arena.root_node_ids()),
+    };
+    scopes.flat_map(|id| arena.descendants(id))
+}
+
+fn is_visible(arena: &ScopedArena<StateData>, state: &StateRef) -> bool {
+    let enclosing_children = state
+        .scope
+        .into_iter()
+        .flat_map(|scope| arena.ancestors(scope).skip(1))
+        .flat_map(|ancestor| arena.children(ancestor))
+        .chain(arena.root_node_ids());
+
+    descendants(arena, state.scope)
+        .chain(enclosing_children)
+        .any(|id| arena[id].get().name == state.name)
+}
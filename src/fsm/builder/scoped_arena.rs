@@ -48,19 +48,6 @@ impl<T> ScopedArena<T> {
         }
     }
 
-    /// Returns an iterator over all descendant nodes from the current scope.
-    /// If no scope is set, iterates from all root nodes.
-    pub fn descendants_from_scope(&self) -> impl Iterator<Item = &Node<T>> + '_ {
-        let scopes = match self.scope {
-            Some(scope_id) => Either::Left(std::iter::once(scope_id)),
-            None => Either::Right(self.root_node_ids()),
-        };
-
-        scopes
-            .flat_map(|id| id.descendants(&self.arena))
-            .map(|node_id| &self.arena[node_id])
-    }
-
     /// Returns an iterator over root nodes (nodes without a parent).
     pub fn root_nodes(&self) -> impl Iterator<Item = &Node<T>> + Clone {
         self.arena.iter().filter(|node| node.parent().is_none())
@@ -94,11 +81,21 @@ impl<T> ScopedArena<T> {
         node_id.children(&self.arena)
     }
 
+    /// Returns an iterator over a node and all of its descendants
+    pub fn descendants(&self, node_id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        node_id.descendants(&self.arena)
+    }
+
     /// Returns an iterator over all ancestors of a node
     pub fn ancestors(&self, node_id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
         node_id.ancestors(&self.arena)
     }
 
+    /// Returns a reference to the underlying Arena.
+    pub fn inner(&self) -> &Arena<T> {
+        &self.arena
+    }
+
     /// Consumes the ScopedArena and returns the underlying Arena.
     pub fn into_inner(self) -> Arena<T> {
         self.arena
@@ -88,3 +88,71 @@ fn sets_deepest_enter_state_on_composite() {
     let composite = fsm.states().find(|s| s.name() == "Composite").unwrap();
     assert_eq!(composite.enter_state().name(), "DeepestEnter");
 }
+
+#[test]
+fn qualified_enter_state_keeps_the_enter_state_of_its_parent() {
+    let mut builder = UmlFsmBuilder::new("TestFSM");
+    builder.add_enter_state("A.B");
+    let a = builder.add_state("A", StateType::Simple);
+    builder.set_scope(Some(a));
+    builder.add_state("First", StateType::Enter);
+    builder.add_state("B", StateType::Simple);
+
+    let fsm = builder.build().unwrap();
+
+    assert_eq!(fsm.enter_state().qualified_name("::"), "A::B");
+    let a = fsm.states().find(|s| s.name() == "A").unwrap();
+    assert_eq!(a.enter_state().name(), "First");
+    let b = a.substates().find(|s| s.name() == "B").unwrap();
+    assert_eq!(b.state_type(), StateType::Simple);
+}
+
+#[test]
+fn qualified_enter_state_of_composite_is_resolved() {
+    let mut builder = UmlFsmBuilder::new("TestFSM");
+    let composite = builder.add_state("Composite", StateType::Enter);
+    builder.set_scope(Some(composite));
+    builder.add_enter_state("Nested.Deep");
+    let nested = builder.add_state("Nested", StateType::Simple);
+    builder.set_scope(Some(nested));
+    builder.add_state("Deep", StateType::Simple);
+
+    let fsm = builder.build().unwrap();
+
+    assert_eq!(
+        fsm.enter_state().qualified_name("::"),
+        "Composite::Nested::Deep"
+    );
+    let nested = fsm.states().find(|s| s.name() == "Nested").unwrap();
+    assert_eq!(nested.enter_state().name(), "Nested");
+}
+
+#[test]
+fn qualified_enter_state_outside_of_composite_fails() {
+    let mut builder = UmlFsmBuilder::new("TestFSM");
+    builder.add_state("Start", StateType::Enter);
+    let sibling = builder.add_state("Sibling", StateType::Simple);
+    builder.set_scope(Some(sibling));
+    builder.add_state("Child", StateType::Simple);
+    builder.set_scope(None);
+    let composite = builder.add_state("Composite", StateType::Simple);
+    builder.set_scope(Some(composite));
+    builder.add_enter_state("Sibling.Child");
+
+    let error = builder.build().unwrap_err();
+
+    assert!(error.to_string().contains("must be one of its substates"));
+}
+
+#[test]
+fn qualified_and_simple_enter_states_fail() {
+    let mut builder = UmlFsmBuilder::new("TestFSM");
+    builder.add_state("Start", StateType::Enter);
+    let a = builder.add_state("A", StateType::Simple);
+    builder.set_scope(Some(a));
+    builder.add_state("B", StateType::Simple);
+    builder.set_scope(None);
+    builder.add_enter_state("A.B");
+
+    assert!(builder.build().is_err());
+}
@@ -2,6 +2,7 @@ mod action_tests;
 mod build_validation_tests;
 mod deferred_event_tests;
 mod enter_state_tests;
+mod resolution_tests;
 mod scoped_arena_tests;
 mod state_tests;
 mod substate_tests;
@@ -0,0 +1,188 @@
+use crate::error::Error;
+use crate::fsm::{Action, State, StateType, TransitionParameters, UmlFsm, UmlFsmBuilder};
+
+#[test]
+fn forward_reference_resolves_to_state_declared_later() {
+    let mut builder = UmlFsmBuilder::new("TestFSM");
+    let parent_a = builder.add_state("ParentA", StateType::Enter);
+    builder.set_scope(Some(parent_a));
+    builder.add_state("A1", StateType::Enter);
+    builder.add_transition(transition("A1", "B1", "toB1"));
+
+    builder.set_scope(None);
+    let parent_b = builder.add_state("ParentB", StateType::Simple);
+    builder.set_scope(Some(parent_b));
+    builder.add_state("B1", StateType::Simple);
+
+    let fsm = builder.build().unwrap();
+
+    assert_eq!(fsm.states().filter(|s| s.name() == "B1").count(), 1);
+    let a1 = find_state(&fsm, "A1");
+    let target = a1.transitions().next().unwrap().destination.unwrap();
+    assert_eq!(target.parent().unwrap().name(), "ParentB");
+}
+
+#[test]
+fn reference_resolves_to_nearest_scope() {
+    let mut builder = UmlFsmBuilder::new("TestFSM");
+    builder.add_state("Child", StateType::Enter);
+    let parent = builder.add_state("Parent", StateType::Simple);
+    builder.set_scope(Some(parent));
+    builder.add_state("Child", StateType::Simple);
+    builder.add_enter_action("Child", Action::from("EnterNestedChild"));
+
+    let fsm = builder.build().unwrap();
+
+    let nested = find_state(&fsm, "Parent").substates().next().unwrap();
+    assert_eq!(
+        nested.enter_action(),
+        Some(&Action::from("EnterNestedChild"))
+    );
+    assert_eq!(fsm.enter_state().enter_action(), None);
+}
+
+#[test]
+fn reference_from_root_prefers_root_state_over_nested_one() {
+    let mut builder = UmlFsmBuilder::new("TestFSM");
+    builder.add_state("Start", StateType::Enter);
+    builder.add_state("Child", StateType::Simple);
+    let parent = builder.add_state("Parent", StateType::Simple);
+    builder.set_scope(Some(parent));
+    builder.add_state("Child", StateType::Simple);
+    builder.set_scope(None);
+    builder.add_transition(transition("Start", "Child", "toChild"));
+
+    let fsm = builder.build().unwrap();
+
+    let start = fsm.enter_state();
+    let target = start.transitions().next().unwrap().destination.unwrap();
+    assert_eq!(target.qualified_name("::"), "Child");
+}
+
+#[test]
+fn ambiguous_reference_fails_listing_candidates() {
+    let mut builder = UmlFsmBuilder::new("TestFSM");
+    builder.add_state("Start", StateType::Enter);
+    for parent in ["ParentA", "ParentB"] {
+        let parent_id = builder.add_state(parent, StateType::Simple);
+        builder.set_scope(Some(parent_id));
+        builder.add_state("Child", StateType::Simple);
+        builder.set_scope(None);
+    }
+    builder.add_transition(transition("Start", "Child", "toChild"));
+
+    let Err(Error::Parse(message)) = builder.build() else {
+        panic!("Expected an ambiguity error");
+    };
+    assert!(message.contains("ParentA::Child"), "{message}");
+    assert!(message.contains("ParentB::Child"), "{message}");
+}
+
+#[test]
+fn implicit_state_is_created_in_scope_of_first_reference() {
+    let mut builder = UmlFsmBuilder::new("TestFSM");
+    builder.add_state("Start", StateType::Enter);
+    builder.add_transition(transition("Start", "Shared", "toShared"));
+    let parent = builder.add_state("Parent", StateType::Simple);
+    builder.set_scope(Some(parent));
+    builder.add_transition(transition("Child", "Shared", "toSharedFromChild"));
+
+    let fsm = builder.build().unwrap();
+
+    let shared: Vec<_> = fsm.states().filter(|s| s.name() == "Shared").collect();
+    assert_eq!(shared.len(), 1);
+    assert!(shared[0].parent().is_none());
+    assert_eq!(find_state(&fsm, "Child").parent().unwrap().name(), "Parent");
+}
+
+#[test]
+fn resolution_does_not_depend_on_declaration_order() {
+    let build = |declare_first: bool| {
+        let mut builder = UmlFsmBuilder::new("TestFSM");
+        builder.add_state("Start", StateType::Enter);
+        if !declare_first {
+            builder.add_transition(transition("Start", "Child", "toChild"));
+        }
+        let parent = builder.add_state("Parent", StateType::Simple);
+        builder.set_scope(Some(parent));
+        builder.add_state("Child", StateType::Simple);
+        builder.set_scope(None);
+        if declare_first {
+            builder.add_transition(transition("Start", "Child", "toChild"));
+        }
+        builder.build().unwrap()
+    };
+
+    assert_eq!(build(true), build(false));
+    let fsm = build(false);
+    assert_eq!(fsm.states().filter(|s| s.name() == "Child").count(), 1);
+}
+
+#[test]
+fn qualified_reference_resolves_ambiguous_name() {
+    let mut builder = UmlFsmBuilder::new("TestFSM");
+    builder.add_state("Start", StateType::Enter);
+    for parent in ["ParentA", "ParentB"] {
+        let parent_id = builder.add_state(parent, StateType::Simple);
+        builder.set_scope(Some(parent_id));
+        builder.add_state("Child", StateType::Simple);
+        builder.set_scope(None);
+    }
+    builder.add_transition(transition("Start", "ParentB.Child", "toChild"));
+    builder.add_exit_action("ParentA::Child", Action::from("ExitChildA"));
+
+    let fsm = builder.build().unwrap();
+
+    let start = fsm.enter_state();
+    let target = start.transitions().next().unwrap().destination.unwrap();
+    assert_eq!(target.qualified_name("::"), "ParentB::Child");
+    let child_a = find_state(&fsm, "ParentA").substates().next().unwrap();
+    assert_eq!(child_a.exit_action(), Some(&Action::from("ExitChildA")));
+}
+
+#[test]
+fn qualified_reference_to_unknown_state_fails() {
+    let mut builder = UmlFsmBuilder::new("TestFSM");
+    builder.add_state("Start", StateType::Enter);
+    builder.add_state("Parent", StateType::Simple);
+    builder.add_transition(transition("Start", "Parent.Missing", "toMissing"));
+
+    assert!(builder.build().is_err());
+}
+
+#[test]
+fn qualified_enter_state_is_resolved() {
+    let mut builder = UmlFsmBuilder::new("TestFSM");
+    let parent = builder.add_state("Parent", StateType::Simple);
+    builder.set_scope(Some(parent));
+    builder.add_state("Child", StateType::Simple);
+    builder.set_scope(None);
+    builder.add_enter_state("Parent.Child");
+
+    let fsm = builder.build().unwrap();
+
+    assert_eq!(fsm.enter_state().qualified_name("::"), "Parent::Child");
+    assert_eq!(find_state(&fsm, "Parent").state_type(), StateType::Simple);
+}
+
+#[test]
+fn qualified_enter_state_of_unknown_state_fails() {
+    let mut builder = UmlFsmBuilder::new("TestFSM");
+    builder.add_enter_state("Parent.Child");
+
+    assert!(builder.build().is_err());
+}
+
+fn transition<'a>(source: &'a str, target: &'a str, event: &str) -> TransitionParameters<'a> {
+    TransitionParameters {
+        source,
+        target: Some(target),
+        event: Some(event.into()),
+        action: None,
+        guard: None,
+    }
+}
+
+fn find_state<'a>(fsm: &'a UmlFsm, name: &str) -> State<'a> {
+    fsm.states().find(|s| s.name() == name).unwrap()
+}
@@ -57,7 +57,7 @@ fn nodes_in_scope_returns_roots_when_unscoped() {
 }
 
 #[test]
-fn descendants_from_scope_traverses_hierarchy() {
+fn descendants_traverses_hierarchy() {
     let mut arena: ScopedArena<&str> = ScopedArena::new();
     let parent = arena.new_node_in_scope("parent");
     arena.set_scope(Some(parent));
@@ -65,7 +65,9 @@ fn descendants_from_scope_traverses_hierarchy() {
     arena.set_scope(Some(child));
     arena.new_node_in_scope("grandchild");
 
-    arena.set_scope(Some(parent));
-    let descendants: Vec<_> = arena.descendants_from_scope().map(|n| *n.get()).collect();
+    let descendants: Vec<_> = arena
+        .descendants(parent)
+        .map(|id| *arena[id].get())
+        .collect();
     assert_eq!(descendants, vec!["parent", "child", "grandchild"]);
 }
@@ -4,7 +4,7 @@ use crate::fsm::types::{Action, Event, StateType};
 
 use super::StateId;
 use super::state::{State, StateData};
-use super::transition::Transition;
+use super::transition::{Transition, TransitionData};
 
 #[derive(Clone)]
 pub struct UmlFsm {
@@ -47,6 +47,13 @@ impl UmlFsm {
                 .map(|t| Transition::from(t, arena))
         })
     }
+
+    /// Removes all transitions for which `keep` returns false
+    pub fn retain_transitions(&mut self, mut keep: impl FnMut(&TransitionData) -> bool) {
+        for node in self.arena.iter_mut() {
+            node.get_mut().transitions.retain(&mut keep);
+        }
+    }
 }
 
 impl PartialEq for UmlFsm {
@@ -69,14 +76,8 @@ impl UmlFsm {
     }
 
     fn states_eq(&self, other: &Self) -> bool {
-        let self_states: HashSet<_> = self
-            .states()
-            .map(|s| (s.name().to_string(), s.state_type()))
-            .collect();
-        let other_states: HashSet<_> = other
-            .states()
-            .map(|s| (s.name().to_string(), s.state_type()))
-            .collect();
+        let self_states: HashSet<_> = self.states().map(state_key).collect();
+        let other_states: HashSet<_> = other.states().map(state_key).collect();
         self_states == other_states
     }
 
@@ -145,6 +146,14 @@ impl UmlFsm {
     }
 }
 
+fn state_key(s: State) -> (String, StateType, Option<String>) {
+    (
+        s.name().to_string(),
+        s.state_type(),
+        s.parent().map(|p| p.name().to_string()),
+    )
+}
+
 fn transition_key(
     t: Transition,
 ) -> (
@@ -30,6 +30,21 @@ impl<'a> State<'a> {
         &self.node_data().name
     }
 
+    /// The names of all parents, starting at the outermost one, followed by the own name
+    pub fn path(&self) -> Vec<&'a str> {
+        let mut names: Vec<_> = self
+            .id
+            .ancestors(self.arena)
+            .map(|id| self.arena[id].get().name.as_str())
+            .collect();
+        names.reverse();
+        names
+    }
+
+    pub fn qualified_name(&self, separator: &str) -> String {
+        self.path().join(separator)
+    }
+
     pub fn state_type(&self) -> StateType {
         self.node_data().state_type
     }
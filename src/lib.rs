@@ -1,13 +1,18 @@
+use std::collections::HashMap;
+
+use heck::ToSnakeCase;
 use proc_macro::TokenStream;
-use quote::quote;
+use quote::quote_spanned;
 
 mod codegen;
 mod error;
 mod file;
 mod fsm;
 mod logging;
+mod markdown;
 mod options;
 mod parser;
+mod source;
 #[cfg(test)]
 mod test;
 
@@ -17,7 +22,8 @@ use crate::codegen::FsmCodeGenerator;
 /// Parse the given FSM definition file and generate the corresponding Rust code.
 ///
 /// The input to this macro is the path a file containing the FSM definition.
-/// Currently, only PlantUML state machine diagrams are supported.
+/// PlantUML and Mermaid (`stateDiagram-v2`) state machine diagrams as well as SCXML documents are
+/// supported.
 /// This will generate an FSM implementation and traits for events and actions, which the use has
 /// to implement.
 ///
@@ -27,13 +33,32 @@ use crate::codegen::FsmCodeGenerator;
 /// #generate_fsm!("path/to/fsm_definition.puml")
 /// // With parameters:
 /// #generate_fsm!(file_path = "path/to/fsm_definition.puml", log_level = "debug")
+/// // With the definition inline:
+/// #generate_fsm!(r#"
+///     @startuml MyFsm
+///     [*] --> A
+///     @enduml
+/// "#)
 ///
 /// # Parameters
 ///
 /// | Parameter | Description |  Default
 /// |-----------|-------------|----------
-/// | **file_path** | Path to the FSM definition file. This parameter is required. | None
-/// | **log_level** | Optional log level for state transitions. Possible values: `error`, `warn`, `info`, `debug`, `trace`. If not set, no logging is performed. | None
+/// | **file_path** | Path to the FSM definition file, relative to `src/` or the manifest directory, to the caller with `./` or `../`, or to the workspace root with a `workspace:` prefix. `$VAR` and `${VAR}` are expanded, e.g. `$OUT_DIR`. Either this or `source` is required. | None
+/// | **source** | The FSM definition as string literal, instead of a file. A bare raw string literal is taken as `source` as well. Errors point at their position within a raw string literal on nightly compilers. On stable, they point at the whole literal and tell the line and column within it. | None
+/// | **format** | Input format, `plantuml`, `mermaid` or `scxml`. Detected by the file extension if not set, `.mmd` files are Mermaid and `.scxml` files SCXML. Mermaid `<<fork>>` and `<<join>>` states are not supported, as there are no orthogonal regions, and result in a compile time error. | None
+/// | **fence** | Name of the fenced code block, e.g. ```` ```plantuml Name ````, if `file_path` is a Markdown (`.md`) or Rust (`.rs`) file with several diagram fences. Without it, all PlantUML fences are used. | None
+/// | **diagram** | Name of the diagram to generate, if the file contains several `@startuml Name ... @enduml` blocks. | None
+/// | **log_level** | Optional log level of the steps of the FSM, e.g. transitions, deferrals and ignored events. Possible values: `error`, `warn`, `info`, `debug`, `trace`. If not set, no logging is performed. | None
+/// | **logger** | The crate to log with, `log` or `defmt`. With `defmt`, the state ids and event kinds implement `defmt::Format` by their names in the diagram, so both log the same messages. Requires `log_level`. | `log`
+/// | **log_target** | Target of the log records. Requires `log_level`, not supported by `defmt`. | The module path of the FSM
+/// | **log_format** | Format of the log messages, `{fsm}` is replaced by the diagram name and `{message}` by the step. Requires `log_level`. | `{fsm}: {message}`
+/// | **fallible_actions** | If `true`, actions return `Result<(), Self::Error>` and the event methods return their error. A failed action enters the target of the `error` transition of the current state or its parents, e.g. `Running --> Fault : error`. | `false`
+/// | **async_actions** | If `true`, actions and guards are `async fn` of the actions trait, and `start()`, `trigger()` and the event methods are `async`. | `false`
+/// | **deferred_capacity** | Number of deferred events stored inline, so that the generated code does not need `alloc`. A deferred event exceeding it is discarded and passed to `on_unhandled`. | None, stored on the heap
+/// | **posted_capacity** | Number of events which actions can post while an event is processed. The actions get a `posted` parameter to post follow-up events, which are processed after the current transition. | None, actions cannot post events
+/// | **serde** | If `true`, `snapshot()` of the FSM and `restore(snapshot, actions)` as well as `restore_with_observer(snapshot, actions, observer)` are generated, to serialize the FSM with [serde](https://docs.rs/serde). Requires a dependency on `serde` with its `derive` feature. | `false`
+/// | **tracing** | Optional level of [tracing](https://docs.rs/tracing) spans for each dispatched event, with events for its steps. Possible values: `error`, `warn`, `info`, `debug`, `trace`. Requires a dependency on `tracing`. | None
 ///
 ///
 /// ```
@@ -41,11 +66,15 @@ use crate::codegen::FsmCodeGenerator;
 ///
 /// | Generated Item | Naming Pattern | Description |
 /// |---------------|----------------|-------------|
-/// | **FSM Struct** | `{DiagramName}` | Main state machine struct (UpperCamelCase) |
+/// | **FSM Struct** | `{DiagramName}` | Main state machine struct (UpperCamelCase), owning the actions, see `actions()` and `stop()` |
 /// | **Event Parameters Trait** | `I{DiagramName}EventParams` | Trait defining event parameter types |
 /// | **Actions Trait** | `I{DiagramName}Actions` | Trait defining action methods |
-/// | **Event Enum** | `{DiagramName}Event` | Enum containing all possible events |
+/// | **Observer Trait** | `I{DiagramName}Observer` | Optional hooks for the steps of the FSM, see `start_with_observer()` |
+/// | **Event Enum** | `{DiagramName}Event` | Enum containing all possible events, see `trigger()` of the FSM Struct |
+/// | **Event Kind Enum** | `{DiagramName}EventKind` | The events without parameters, with an `ALL` constant |
+/// | **Outcome Enum** | `{DiagramName}Outcome` | Returned by the event methods, e.g. `Transitioned { from, to }` or `Ignored` |
 /// | **State Struct** | `{DiagramName}State` | Internal state representation |
+/// | **State Id Enum** | `{DiagramName}StateId` | Public state ids, see `current_state()`, `active_states()` and `is_in()` of the FSM Struct |
 /// | **Module** | `{diagram_name}` | Generated module name (snake_case) |
 ///
 /// # Example
@@ -72,32 +101,272 @@ use crate::codegen::FsmCodeGenerator;
 ///
 /// let actions = MyActions;
 /// let mut fsm = MyFsm::new(actions);
-/// fsm.trigger_event(MyFsmEvent::SomeEvent(()));
-/// fsm.trigger_event(MyFsmEvent::OtherEvent("data".to_string()));
+/// let _ = fsm.some_event(());
+/// let _ = fsm.trigger(MyFsmEvent::OtherEvent("data".to_string()));
 /// ```
 pub fn generate_fsm(input: TokenStream) -> TokenStream {
-    match generate_fsm_inner(input) {
-        Ok(tokens) => tokens,
+    generate(input, generate_fsm_code)
+}
+
+#[proc_macro]
+/// Parse all diagrams of the given FSM definition file and generate a module for each of them.
+///
+/// Takes the same parameters as [`generate_fsm!`], except for `diagram`. Instead of a single file,
+/// all files of a directory may be generated:
+///
+/// | Parameter | Description |  Default
+/// |-----------|-------------|----------
+/// | **dir** | Path to a directory of FSM definition files, resolved like `file_path`. Cargo tracks the files that were read, but not the directory itself: after adding or removing a file, touch the Rust source invoking the macro to expand it again. | None
+/// | **glob** | Pattern of the files within `dir`, e.g. `**/*.puml` to include subdirectories. | `*.puml`, `*.mmd` for `format = "mermaid"`, `*.scxml` for `format = "scxml"`
+///
+/// The diagrams must have distinct names, also across files. As the modules are named in
+/// snake_case, e.g. `MyFsm` and `my_fsm` are the same name.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use phyto_fsm::generate_fsms;
+/// // Contains `@startuml Door ... @enduml` and `@startuml Light ... @enduml`
+/// generate_fsms!("path/to/fsm_definitions.puml");
+/// // A module for every `.puml` file of the directory
+/// generate_fsms!(dir = "fsm/");
+///
+/// use door::*;
+/// use light::*;
+/// ```
+pub fn generate_fsms(input: TokenStream) -> TokenStream {
+    generate(input, generate_fsms_code)
+}
+
+fn generate(
+    input: TokenStream,
+    generate_code: fn(&options::Options) -> error::Result<proc_macro2::TokenStream>,
+) -> TokenStream {
+    logging::init();
+    let options = match syn::parse::<options::Options>(input) {
+        Ok(options) => options,
+        Err(e) => {
+            let error = error::Error::InvalidInput(e.to_string());
+            return compile_error(&error.to_string(), proc_macro2::Span::call_site());
+        }
+    };
+
+    match generate_code(&options) {
+        Ok(tokens) => tokens.into(),
         Err(error) => {
-            let error_msg = format!("[phyto-fsm] {}", error);
-            quote! {
-                compile_error!(#error_msg);
-            }
-            .into()
+            let (span, message) = match &options.source {
+                source::Source::Inline(source) => source.locate(&error),
+                source::Source::File(_) | source::Source::Dir(_) => {
+                    (proc_macro2::Span::call_site(), error.to_string())
+                }
+            };
+            compile_error(&message, span)
         }
     }
 }
 
-fn generate_fsm_inner(input: TokenStream) -> error::Result<TokenStream> {
-    logging::init();
+fn compile_error(message: &str, span: proc_macro2::Span) -> TokenStream {
+    let error_msg = format!("[phyto-fsm] {}", message);
+    quote_spanned! {span=>
+        compile_error!(#error_msg);
+    }
+    .into()
+}
+
+fn generate_fsm_code(options: &options::Options) -> error::Result<proc_macro2::TokenStream> {
+    let source = read_source(options)?;
+    let parsed_fsm = match &options.diagram {
+        Some(name) => fsm::UmlFsm::try_parse_diagram(&source.content, source.format, name)?,
+        None => fsm::UmlFsm::try_parse(&source.content, source.format)?,
+    };
+    let generator = FsmCodeGenerator::new(&options.codegen);
+    let fsm = generator.generate(parsed_fsm)?;
+    let dependencies = source.dependencies;
+    Ok(quote::quote! { #dependencies #fsm })
+}
+
+fn generate_fsms_code(options: &options::Options) -> error::Result<proc_macro2::TokenStream> {
+    if options.diagram.is_some() {
+        return Err(error::Error::InvalidInput(
+            "The 'diagram' option is not supported, all diagrams of the file are generated"
+                .to_string(),
+        ));
+    }
+    let sources = match &options.source {
+        source::Source::Dir(path) => read_dir(path, options)?,
+        _ => vec![read_source(options)?],
+    };
+    let in_dir = matches!(options.source, source::Source::Dir(_));
 
-    let options: options::Options =
-        syn::parse(input).map_err(|e| error::Error::InvalidInput(e.to_string()))?;
-    let file_path = file::FilePath::resolve(&options.file_path, proc_macro::Span::call_site());
-    let file = file::FsmFile::try_open(file_path)?;
-    let parsed_fsm = fsm::UmlFsm::try_parse(file.content())?;
     let generator = FsmCodeGenerator::new(&options.codegen);
-    let fsm_code = generator.generate(parsed_fsm);
+    let mut code = proc_macro2::TokenStream::new();
+    for parsed_fsm in parse_sources(&sources, in_dir)? {
+        code.extend(generator.generate(parsed_fsm)?);
+    }
+    code.extend(sources.into_iter().map(|source| source.dependencies));
+    Ok(code)
+}
 
-    Ok(fsm_code.into())
+/// Parses all diagrams of the sources, whose modules must have distinct names across the
+/// sources. Errors of the files of a directory name the file.
+fn parse_sources(sources: &[DiagramSource], in_dir: bool) -> error::Result<Vec<fsm::UmlFsm>> {
+    let mut modules = HashMap::new();
+    let mut parsed = vec![];
+    for source in sources {
+        let parsed_fsms = fsm::UmlFsm::try_parse_all(&source.content, source.format).map_err(
+            |e| match in_dir {
+                true => error::Error::InvalidFile(source.origin.clone(), e.to_string()),
+                false => e,
+            },
+        )?;
+        for parsed_fsm in parsed_fsms {
+            let name = parsed_fsm.name();
+            let module = name.to_snake_case();
+            if let Some((other_name, other_origin)) =
+                modules.insert(module.clone(), (name.to_string(), &source.origin))
+            {
+                return Err(error::Error::Parse(format!(
+                    "Diagrams '{other_name}' in {other_origin} and '{name}' in {} generate the same module '{module}'",
+                    source.origin
+                )));
+            }
+            parsed.push(parsed_fsm);
+        }
+    }
+    Ok(parsed)
+}
+
+/// The diagram source, read from a file or given inline
+struct DiagramSource {
+    /// The file path, for error messages
+    origin: String,
+    content: String,
+    format: parser::Format,
+    /// Includes of the read files, so that cargo tracks them, see [`file::FsmFile::dependency`]
+    dependencies: proc_macro2::TokenStream,
+}
+
+impl From<file::FsmFile> for DiagramSource {
+    fn from(file: file::FsmFile) -> Self {
+        Self {
+            origin: file.path().to_string(),
+            content: file.content().to_string(),
+            format: file.format(),
+            dependencies: file.dependency(),
+        }
+    }
+}
+
+/// Reads the diagram source and its format, inline sources are PlantUML unless specified
+fn read_source(options: &options::Options) -> error::Result<DiagramSource> {
+    match &options.source {
+        source::Source::File(path) => {
+            let file_path = file::FilePath::resolve(path, proc_macro::Span::call_site())?;
+            let file =
+                file::FsmFile::try_open(file_path, options.format, options.fence.as_deref())?;
+            Ok(file.into())
+        }
+        source::Source::Inline(_) if options.fence.is_some() => Err(error::Error::InvalidInput(
+            "The 'fence' option requires a Markdown (.md) or Rust (.rs) file".to_string(),
+        )),
+        source::Source::Inline(source) => Ok(DiagramSource {
+            origin: "inline source".to_string(),
+            content: source.content(),
+            format: options.format.unwrap_or_default(),
+            dependencies: proc_macro2::TokenStream::new(),
+        }),
+        source::Source::Dir(_) => Err(error::Error::InvalidInput(
+            "The 'dir' option is only supported by generate_fsms!".to_string(),
+        )),
+    }
+}
+
+/// Reads the files of the directory matching the glob, by default the files of the format
+fn read_dir(path: &str, options: &options::Options) -> error::Result<Vec<DiagramSource>> {
+    let dir = file::FilePath::resolve(path, proc_macro::Span::call_site())?;
+    let glob = options
+        .glob
+        .as_deref()
+        .unwrap_or(options.format.unwrap_or_default().glob());
+    let files = dir.find_files(glob)?;
+    if files.is_empty() {
+        return Err(error::Error::InvalidFile(
+            dir.to_string(),
+            format!("No files matching '{glob}'"),
+        ));
+    }
+    files
+        .into_iter()
+        .map(|file_path| {
+            file::FsmFile::try_open(file_path, options.format, options.fence.as_deref())
+                .map(DiagramSource::from)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagram_source(origin: &str, content: &str) -> DiagramSource {
+        DiagramSource {
+            origin: origin.to_string(),
+            content: content.to_string(),
+            format: parser::Format::PlantUml,
+            dependencies: proc_macro2::TokenStream::new(),
+        }
+    }
+
+    #[test]
+    fn error_on_duplicate_diagram_names_across_sources() {
+        let sources = [
+            diagram_source("door.puml", "@startuml Door\n[*] --> Closed\n@enduml"),
+            diagram_source("light.puml", "@startuml Light\n[*] --> Off\n@enduml"),
+            diagram_source("other_door.puml", "@startuml Door\n[*] --> Open\n@enduml"),
+        ];
+
+        let error = parse_sources(&sources, true).unwrap_err();
+
+        assert_eq!(
+            error,
+            error::Error::Parse(
+                "Diagrams 'Door' in door.puml and 'Door' in other_door.puml generate the same module 'door'"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn error_on_diagram_names_of_the_same_module() {
+        let sources = [
+            diagram_source("my_fsm.puml", "@startuml MyFsm\n[*] --> A\n@enduml"),
+            diagram_source("other.puml", "@startuml my_fsm\n[*] --> A\n@enduml"),
+        ];
+
+        let error = parse_sources(&sources, true).unwrap_err();
+
+        assert_eq!(
+            error,
+            error::Error::Parse(
+                "Diagrams 'MyFsm' in my_fsm.puml and 'my_fsm' in other.puml generate the same module 'my_fsm'"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn diagrams_of_all_sources_are_parsed() {
+        let sources = [
+            diagram_source("door.puml", "@startuml Door\n[*] --> Closed\n@enduml"),
+            diagram_source("light.puml", "@startuml Light\n[*] --> Off\n@enduml"),
+        ];
+
+        let names = parse_sources(&sources, true)
+            .unwrap()
+            .iter()
+            .map(|fsm| fsm.name().to_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(names, vec!["Door", "Light"]);
+    }
 }
@@ -0,0 +1,269 @@
+use std::ops::Range;
+
+use itertools::Itertools;
+
+use crate::error::{Error, Result};
+use crate::parser::Format;
+
+/// Kind of document containing the fenced diagrams
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Document {
+    Markdown,
+    /// Rust source, of which the fences within `///` and `//!` doc comments are used
+    Rustdoc,
+}
+
+impl Document {
+    pub fn from_extension(extension: Option<&str>) -> Option<Self> {
+        match extension {
+            Some("md" | "markdown") => Some(Document::Markdown),
+            Some("rs") => Some(Document::Rustdoc),
+            _ => None,
+        }
+    }
+}
+
+/// A fenced code block with a diagram, e.g. ```` ```plantuml Name ````
+#[derive(Debug)]
+struct Fence<'a> {
+    format: Format,
+    name: Option<&'a str>,
+    indent: usize,
+    /// Line indices of the content, without the fence delimiters
+    lines: Range<usize>,
+}
+
+/// Extracts the diagram fences of the given format and name. All other lines are blanked, so that
+/// line numbers in parse errors match the document. Multiple PlantUML fences are kept as multiple
+/// diagrams.
+pub fn extract(
+    content: &str,
+    document: Document,
+    format: Option<Format>,
+    name: Option<&str>,
+) -> Result<(String, Format)> {
+    let lines: Vec<&str> = content
+        .lines()
+        .map(|line| match document {
+            Document::Markdown => line,
+            Document::Rustdoc => doc_comment(line).unwrap_or_default(),
+        })
+        .collect();
+
+    let fences = find_fences(&lines);
+    let selected: Vec<&Fence> = fences
+        .iter()
+        .filter(|fence| format.is_none_or(|f| f == fence.format))
+        .filter(|fence| name.is_none_or(|n| fence.name == Some(n)))
+        .collect();
+
+    if selected.is_empty() {
+        return Err(no_fence_error(&fences, name));
+    }
+    let format = selected
+        .iter()
+        .map(|fence| fence.format)
+        .dedup()
+        .exactly_one()
+        .map_err(|_| {
+            Error::Parse(
+                "Found PlantUML and Mermaid fences, select one with the 'fence' or 'format' option"
+                    .to_string(),
+            )
+        })?;
+    if format == Format::Mermaid && selected.len() > 1 {
+        return Err(Error::Parse(format!(
+            "Found {} Mermaid fences, select one with the 'fence' option",
+            selected.len()
+        )));
+    }
+
+    let extracted = lines
+        .iter()
+        .enumerate()
+        .map(|(idx, line)| {
+            selected
+                .iter()
+                .find(|fence| fence.lines.contains(&idx))
+                .map_or("", |fence| strip_indent(line, fence.indent))
+        })
+        .join("\n");
+    Ok((extracted, format))
+}
+
+fn no_fence_error(fences: &[Fence<'_>], name: Option<&str>) -> Error {
+    let names = fences.iter().filter_map(|fence| fence.name).join(", ");
+    match name {
+        Some(name) => Error::Parse(format!(
+            "No diagram fence named '{name}' found, available fences are: {names}"
+        )),
+        None => Error::Parse("No ```plantuml or ```mermaid fence found".to_string()),
+    }
+}
+
+fn find_fences<'a>(lines: &[&'a str]) -> Vec<Fence<'a>> {
+    let mut fences = Vec::new();
+    let mut idx = 0;
+    while idx < lines.len() {
+        let Some((delimiter, indent, info)) = opening_fence(lines[idx]) else {
+            idx += 1;
+            continue;
+        };
+        let start = idx + 1;
+        let end = (start..lines.len())
+            .find(|&i| is_closing_fence(lines[i], delimiter))
+            .unwrap_or(lines.len());
+
+        let mut words = info.split_whitespace();
+        let format = match words.next() {
+            Some("plantuml" | "puml") => Some(Format::PlantUml),
+            Some("mermaid") => Some(Format::Mermaid),
+            _ => None,
+        };
+        if let Some(format) = format {
+            fences.push(Fence {
+                format,
+                name: words.next(),
+                indent,
+                lines: start..end,
+            });
+        }
+        idx = end + 1;
+    }
+    fences
+}
+
+/// The delimiter, indentation and info string of an opening fence
+fn opening_fence(line: &str) -> Option<(&str, usize, &str)> {
+    let trimmed = line.trim_start();
+    let indent = line.len() - trimmed.len();
+    let fence_char = trimmed.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let length = trimmed.chars().take_while(|c| *c == fence_char).count();
+    if length < 3 || indent > 3 {
+        return None;
+    }
+    let (delimiter, info) = trimmed.split_at(length);
+    Some((delimiter, indent, info.trim()))
+}
+
+fn is_closing_fence(line: &str, delimiter: &str) -> bool {
+    let trimmed = line.trim();
+    trimmed.starts_with(delimiter) && trimmed.chars().all(|c| delimiter.starts_with(c))
+}
+
+fn strip_indent(line: &str, indent: usize) -> &str {
+    let whitespace = line.len() - line.trim_start().len();
+    &line[whitespace.min(indent)..]
+}
+
+/// The text of a `///` or `//!` doc comment line
+fn doc_comment(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start();
+    let text = trimmed
+        .strip_prefix("///")
+        .filter(|text| !text.starts_with('/'))
+        .or_else(|| trimmed.strip_prefix("//!"))?;
+    Some(text.strip_prefix(' ').unwrap_or(text))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Document, extract};
+    use crate::parser::Format;
+
+    const MARKDOWN: &str = r#"# Design
+
+```plantuml Door
+@startuml Door
+[*] --> Closed
+@enduml
+```
+
+Some text
+
+~~~puml Light
+@startuml Light
+[*] --> Off
+@enduml
+~~~
+
+```rust
+let x = 1;
+```
+"#;
+
+    #[test]
+    fn extract_all_plantuml_fences() {
+        let (content, format) = extract(MARKDOWN, Document::Markdown, None, None).unwrap();
+        assert_eq!(format, Format::PlantUml);
+        let lines: Vec<_> = content.lines().collect();
+        assert_eq!(lines[3], "@startuml Door");
+        assert_eq!(lines[11], "@startuml Light");
+        assert!(!content.contains("Some text"));
+        assert!(!content.contains("let x"));
+    }
+
+    #[test]
+    fn extract_named_fence() {
+        let (content, _) = extract(MARKDOWN, Document::Markdown, None, Some("Light")).unwrap();
+        assert!(content.contains("@startuml Light"));
+        assert!(!content.contains("@startuml Door"));
+    }
+
+    #[test]
+    fn error_on_unknown_fence() {
+        let result = extract(MARKDOWN, Document::Markdown, None, Some("Window"));
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Parse error: No diagram fence named 'Window' found, available fences are: Door, Light"
+        );
+    }
+
+    #[test]
+    fn extract_mermaid_fence() {
+        let markdown = "```mermaid\nstateDiagram-v2\n    [*] --> A\n```\n";
+        let (content, format) = extract(markdown, Document::Markdown, None, None).unwrap();
+        assert_eq!(format, Format::Mermaid);
+        assert_eq!(content, "\nstateDiagram-v2\n    [*] --> A\n");
+    }
+
+    #[test]
+    fn error_on_ambiguous_fences() {
+        let markdown = "```mermaid\nstateDiagram-v2\n```\n```plantuml\n@startuml A\n@enduml\n```\n";
+        assert!(extract(markdown, Document::Markdown, None, None).is_err());
+        let (_, format) =
+            extract(markdown, Document::Markdown, Some(Format::PlantUml), None).unwrap();
+        assert_eq!(format, Format::PlantUml);
+    }
+
+    #[test]
+    fn extract_from_rustdoc() {
+        let rust = r#"
+//! The door of the house
+//!
+//! ```plantuml
+//! @startuml Door
+//! [*] --> Closed
+//! @enduml
+//! ```
+//// Not a doc comment
+fn main() {}
+"#;
+        let (content, format) = extract(rust, Document::Rustdoc, None, None).unwrap();
+        assert_eq!(format, Format::PlantUml);
+        assert_eq!(
+            content
+                .lines()
+                .filter(|l| !l.is_empty())
+                .collect::<Vec<_>>(),
+            vec!["@startuml Door", "[*] --> Closed", "@enduml"]
+        );
+    }
+
+    #[test]
+    fn strip_fence_indentation() {
+        let markdown = "- Item\n\n  ```plantuml\n  @startuml A\n    [*] --> B\n  @enduml\n  ```\n";
+        let (content, _) = extract(markdown, Document::Markdown, None, None).unwrap();
+        assert!(content.contains("\n@startuml A\n  [*] --> B\n@enduml"));
+    }
+}
@@ -4,21 +4,36 @@ use syn::{
     parse::{Parse, ParseStream},
 };
 
-use crate::codegen;
+use crate::{
+    codegen,
+    parser::Format,
+    source::{InlineSource, Source},
+};
 
 pub struct Options {
-    pub file_path: String,
+    pub source: Source,
+    pub diagram: Option<String>,
+    pub fence: Option<String>,
+    /// Pattern of the files within [`Source::Dir`]
+    pub glob: Option<String>,
+    pub format: Option<Format>,
     pub codegen: codegen::Options,
 }
 
 impl Options {
-    fn try_from_file_path(lit: &LitStr) -> syn::Result<Self> {
-        let file_path = lit.value();
-        if file_path.trim().is_empty() {
-            return Err(syn::Error::new(lit.span(), "File path cannot be empty"));
-        }
+    /// A bare raw string literal is the diagram source, any other string literal a file path
+    fn try_from_literal(lit: &LitStr) -> syn::Result<Self> {
+        let source = if is_raw(lit) {
+            Source::Inline(InlineSource::new(lit.clone()))
+        } else {
+            Source::File(parse_file_path(lit)?)
+        };
         Ok(Self {
-            file_path,
+            source,
+            diagram: None,
+            fence: None,
+            glob: None,
+            format: None,
             codegen: codegen::Options::default(),
         })
     }
@@ -26,47 +41,149 @@ impl Options {
     fn try_from_key_value_pairs(input: ParseStream) -> syn::Result<Self> {
         let parsed_pairs =
             syn::punctuated::Punctuated::<OptionKeyValue, syn::Token![,]>::parse_terminated(input)?;
-        let file_path = parsed_pairs
+        let source = parsed_pairs
             .iter()
-            .filter_map(|pair| {
-                if let OptionKeyValue::FilePath(path) = pair {
-                    Some(path)
-                } else {
-                    None
-                }
+            .filter_map(|pair| match pair {
+                OptionKeyValue::FilePath(path) => Some(Source::File(path.clone())),
+                OptionKeyValue::Source(lit) => Some(Source::Inline(InlineSource::new(lit.clone()))),
+                OptionKeyValue::Dir(path) => Some(Source::Dir(path.clone())),
+                _ => None,
             })
             .exactly_one()
             .map_err(|_| {
                 syn::Error::new(
                     input.span(),
-                    "Expected exactly one 'file_path' key in options",
+                    "Expected exactly one 'file_path', 'source' or 'dir' key in options",
                 )
             })?;
 
-        let log_level = parsed_pairs
-            .iter()
-            .filter_map(|pair| {
-                if let OptionKeyValue::LogLevel(level) = pair {
-                    Some(*level)
-                } else {
-                    None
-                }
-            })
-            .at_most_one()
-            .map_err(|_| {
-                syn::Error::new(
-                    input.span(),
-                    "Expected at most one 'log_level' key in options",
-                )
+        let log_level = at_most_one(&parsed_pairs, input, "log_level", |pair| match pair {
+            OptionKeyValue::LogLevel(level) => Some(*level),
+            _ => None,
+        })?;
+        let log_target = at_most_one(&parsed_pairs, input, "log_target", |pair| match pair {
+            OptionKeyValue::LogTarget(target) => Some(target.clone()),
+            _ => None,
+        })?;
+        let log_format = at_most_one(&parsed_pairs, input, "log_format", |pair| match pair {
+            OptionKeyValue::LogFormat(format) => Some(format.clone()),
+            _ => None,
+        })?;
+        let logger = at_most_one(&parsed_pairs, input, "logger", |pair| match pair {
+            OptionKeyValue::Logger(logger) => Some(*logger),
+            _ => None,
+        })?;
+        if log_level.is_none() && (log_target.is_some() || log_format.is_some() || logger.is_some())
+        {
+            return Err(syn::Error::new(
+                input.span(),
+                "The 'logger', 'log_target' and 'log_format' keys require a 'log_level' key in options",
+            ));
+        }
+        if logger == Some(codegen::Logger::Defmt) && log_target.is_some() {
+            return Err(syn::Error::new(
+                input.span(),
+                "The 'log_target' key is not supported by the 'defmt' logger",
+            ));
+        }
+        let diagram = at_most_one(&parsed_pairs, input, "diagram", |pair| match pair {
+            OptionKeyValue::Diagram(name) => Some(name.clone()),
+            _ => None,
+        })?;
+        let fence = at_most_one(&parsed_pairs, input, "fence", |pair| match pair {
+            OptionKeyValue::Fence(name) => Some(name.clone()),
+            _ => None,
+        })?;
+        let glob = at_most_one(&parsed_pairs, input, "glob", |pair| match pair {
+            OptionKeyValue::Glob(pattern) => Some(pattern.clone()),
+            _ => None,
+        })?;
+        if glob.is_some() && !matches!(source, Source::Dir(_)) {
+            return Err(syn::Error::new(
+                input.span(),
+                "The 'glob' key requires a 'dir' key in options",
+            ));
+        }
+        let format = at_most_one(&parsed_pairs, input, "format", |pair| match pair {
+            OptionKeyValue::Format(format) => Some(*format),
+            _ => None,
+        })?;
+        let fallible_actions =
+            at_most_one(
+                &parsed_pairs,
+                input,
+                "fallible_actions",
+                |pair| match pair {
+                    OptionKeyValue::FallibleActions(fallible) => Some(*fallible),
+                    _ => None,
+                },
+            )?;
+        let async_actions =
+            at_most_one(&parsed_pairs, input, "async_actions", |pair| match pair {
+                OptionKeyValue::AsyncActions(asynchronous) => Some(*asynchronous),
+                _ => None,
+            })?;
+        let deferred_capacity =
+            at_most_one(
+                &parsed_pairs,
+                input,
+                "deferred_capacity",
+                |pair| match pair {
+                    OptionKeyValue::DeferredCapacity(capacity) => Some(*capacity),
+                    _ => None,
+                },
+            )?;
+        let posted_capacity =
+            at_most_one(&parsed_pairs, input, "posted_capacity", |pair| match pair {
+                OptionKeyValue::PostedCapacity(capacity) => Some(*capacity),
+                _ => None,
             })?;
 
+        let tracing_level = at_most_one(&parsed_pairs, input, "tracing", |pair| match pair {
+            OptionKeyValue::Tracing(level) => Some(*level),
+            _ => None,
+        })?;
+        let serde = at_most_one(&parsed_pairs, input, "serde", |pair| match pair {
+            OptionKeyValue::Serde(serde) => Some(*serde),
+            _ => None,
+        })?;
+
         Ok(Self {
-            file_path: file_path.clone(),
-            codegen: codegen::Options { log_level },
+            source,
+            diagram,
+            fence,
+            glob,
+            format,
+            codegen: codegen::Options {
+                log_level,
+                logger: logger.unwrap_or_default(),
+                log_target,
+                log_format,
+                tracing_level,
+                fallible_actions: fallible_actions.unwrap_or_default(),
+                async_actions: async_actions.unwrap_or_default(),
+                deferred_capacity,
+                posted_capacity,
+                serde: serde.unwrap_or_default(),
+            },
         })
     }
 }
 
+fn at_most_one<T>(
+    pairs: &syn::punctuated::Punctuated<OptionKeyValue, syn::Token![,]>,
+    input: ParseStream,
+    key: &str,
+    value: impl Fn(&OptionKeyValue) -> Option<T>,
+) -> syn::Result<Option<T>> {
+    pairs.iter().filter_map(value).at_most_one().map_err(|_| {
+        syn::Error::new(
+            input.span(),
+            format!("Expected at most one '{key}' key in options"),
+        )
+    })
+}
+
 impl Parse for Options {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         if input.is_empty() {
@@ -74,8 +191,8 @@ impl Parse for Options {
         }
 
         if input.peek(syn::LitStr) {
-            let file_path: LitStr = input.parse()?;
-            return Options::try_from_file_path(&file_path);
+            let lit: LitStr = input.parse()?;
+            return Options::try_from_literal(&lit);
         }
 
         Options::try_from_key_value_pairs(input)
@@ -84,7 +201,22 @@ impl Parse for Options {
 
 enum OptionKeyValue {
     FilePath(String),
+    Source(LitStr),
+    Dir(String),
+    Glob(String),
+    Diagram(String),
+    Fence(String),
+    Format(Format),
     LogLevel(log::Level),
+    Logger(codegen::Logger),
+    LogTarget(String),
+    LogFormat(String),
+    Tracing(log::Level),
+    FallibleActions(bool),
+    AsyncActions(bool),
+    DeferredCapacity(usize),
+    PostedCapacity(usize),
+    Serde(bool),
 }
 
 impl Parse for OptionKeyValue {
@@ -94,11 +226,39 @@ impl Parse for OptionKeyValue {
         match key.to_string().as_str() {
             "file_path" => {
                 let lit: LitStr = input.parse()?;
-                let file_path = lit.value();
-                if file_path.trim().is_empty() {
-                    return Err(syn::Error::new(lit.span(), "File path cannot be empty"));
+                Ok(OptionKeyValue::FilePath(parse_file_path(&lit)?))
+            }
+            "source" => Ok(OptionKeyValue::Source(input.parse()?)),
+            "dir" => {
+                let lit: LitStr = input.parse()?;
+                Ok(OptionKeyValue::Dir(parse_file_path(&lit)?))
+            }
+            "glob" => {
+                let lit: LitStr = input.parse()?;
+                glob::Pattern::new(&lit.value())
+                    .map_err(|e| syn::Error::new(lit.span(), format!("Invalid glob: {e}")))?;
+                Ok(OptionKeyValue::Glob(lit.value()))
+            }
+            "diagram" => {
+                let lit: LitStr = input.parse()?;
+                let name = lit.value();
+                if name.trim().is_empty() {
+                    return Err(syn::Error::new(lit.span(), "Diagram name cannot be empty"));
                 }
-                Ok(OptionKeyValue::FilePath(file_path))
+                Ok(OptionKeyValue::Diagram(name))
+            }
+            "fence" => {
+                let lit: LitStr = input.parse()?;
+                let name = lit.value();
+                if name.trim().is_empty() {
+                    return Err(syn::Error::new(lit.span(), "Fence name cannot be empty"));
+                }
+                Ok(OptionKeyValue::Fence(name))
+            }
+            "format" => {
+                let lit: LitStr = input.parse()?;
+                let format = parse_format(&lit.value(), lit.span())?;
+                Ok(OptionKeyValue::Format(format))
             }
             "log_level" => {
                 let lit: LitStr = input.parse()?;
@@ -106,14 +266,112 @@ impl Parse for OptionKeyValue {
                 let log_level = parse_log_level(&level_str, lit.span())?;
                 Ok(OptionKeyValue::LogLevel(log_level))
             }
+            "logger" => {
+                let lit: LitStr = input.parse()?;
+                let logger = parse_logger(&lit.value(), lit.span())?;
+                Ok(OptionKeyValue::Logger(logger))
+            }
+            "log_target" => {
+                let lit: LitStr = input.parse()?;
+                let target = lit.value();
+                if target.trim().is_empty() {
+                    return Err(syn::Error::new(lit.span(), "Log target cannot be empty"));
+                }
+                Ok(OptionKeyValue::LogTarget(target))
+            }
+            "log_format" => {
+                let lit: LitStr = input.parse()?;
+                let format = lit.value();
+                if !format.contains("{message}") {
+                    return Err(syn::Error::new(
+                        lit.span(),
+                        "Log format must contain the '{message}' placeholder",
+                    ));
+                }
+                Ok(OptionKeyValue::LogFormat(format))
+            }
+            "tracing" => {
+                let lit: LitStr = input.parse()?;
+                let level = parse_log_level(&lit.value(), lit.span())?;
+                Ok(OptionKeyValue::Tracing(level))
+            }
+            "fallible_actions" => {
+                let lit: syn::LitBool = input.parse()?;
+                Ok(OptionKeyValue::FallibleActions(lit.value))
+            }
+            "async_actions" => {
+                let lit: syn::LitBool = input.parse()?;
+                Ok(OptionKeyValue::AsyncActions(lit.value))
+            }
+            "deferred_capacity" => {
+                let lit: syn::LitInt = input.parse()?;
+                let capacity: usize = lit.base10_parse()?;
+                if capacity == 0 {
+                    return Err(syn::Error::new(
+                        lit.span(),
+                        "Deferred capacity must be at least 1",
+                    ));
+                }
+                Ok(OptionKeyValue::DeferredCapacity(capacity))
+            }
+            "posted_capacity" => {
+                let lit: syn::LitInt = input.parse()?;
+                let capacity: usize = lit.base10_parse()?;
+                if capacity == 0 {
+                    return Err(syn::Error::new(
+                        lit.span(),
+                        "Posted capacity must be at least 1",
+                    ));
+                }
+                Ok(OptionKeyValue::PostedCapacity(capacity))
+            }
+            "serde" => {
+                let lit: syn::LitBool = input.parse()?;
+                Ok(OptionKeyValue::Serde(lit.value))
+            }
             _ => Err(syn::Error::new(
                 key.span(),
-                "Unknown option key. Expected 'file_path' or 'log_level'",
+                "Unknown option key. Expected 'file_path', 'source', 'dir', 'glob', 'diagram', 'fence', 'format', 'log_level', 'logger', 'log_target', 'log_format', 'tracing', 'fallible_actions', 'async_actions', 'deferred_capacity', 'posted_capacity' or 'serde'",
             )),
         }
     }
 }
 
+fn parse_file_path(lit: &LitStr) -> syn::Result<String> {
+    let file_path = lit.value();
+    if file_path.trim().is_empty() {
+        return Err(syn::Error::new(lit.span(), "File path cannot be empty"));
+    }
+    Ok(file_path)
+}
+
+fn is_raw(lit: &LitStr) -> bool {
+    lit.token().to_string().starts_with('r')
+}
+
+fn parse_format(format: &str, span: proc_macro2::Span) -> syn::Result<Format> {
+    match format.to_lowercase().as_str() {
+        "plantuml" => Ok(Format::PlantUml),
+        "mermaid" => Ok(Format::Mermaid),
+        "scxml" => Ok(Format::Scxml),
+        _ => Err(syn::Error::new(
+            span,
+            "Invalid format. Expected one of: plantuml, mermaid, scxml",
+        )),
+    }
+}
+
+fn parse_logger(logger: &str, span: proc_macro2::Span) -> syn::Result<codegen::Logger> {
+    match logger.to_lowercase().as_str() {
+        "log" => Ok(codegen::Logger::Log),
+        "defmt" => Ok(codegen::Logger::Defmt),
+        _ => Err(syn::Error::new(
+            span,
+            "Invalid logger. Expected one of: log, defmt",
+        )),
+    }
+}
+
 fn parse_log_level(level: &str, span: proc_macro2::Span) -> syn::Result<log::Level> {
     match level.to_lowercase().as_str() {
         "error" => Ok(log::Level::Error),
@@ -139,10 +397,24 @@ mod test {
         Options::parse.parse2(token_stream)
     }
 
+    fn assert_file_path(options: &Options, expected: &str) {
+        match &options.source {
+            Source::File(path) => assert_eq!(path, expected),
+            _ => panic!("Expected a file path"),
+        }
+    }
+
+    fn assert_inline_source(options: &Options, expected: &str) {
+        match &options.source {
+            Source::Inline(source) => assert_eq!(source.content(), expected),
+            _ => panic!("Expected an inline source"),
+        }
+    }
+
     #[test]
     fn parse_file_path_only() {
         let options = try_parse_file_path("path/to/fsm.puml").unwrap();
-        assert_eq!(options.file_path, "path/to/fsm.puml");
+        assert_file_path(&options, "path/to/fsm.puml");
         assert_eq!(options.codegen.log_level, None);
     }
 
@@ -159,7 +431,7 @@ mod test {
     fn parse_file_path_as_key_value() {
         let tokens = quote::quote!(file_path = "path/to/fsm.puml");
         let options = Options::parse.parse2(tokens).unwrap();
-        assert_eq!(options.file_path, "path/to/fsm.puml");
+        assert_file_path(&options, "path/to/fsm.puml");
         assert_eq!(options.codegen.log_level, None);
     }
 
@@ -167,7 +439,7 @@ mod test {
     fn parse_key_value_pairs() {
         let tokens = quote::quote!(file_path = "path/to/fsm.puml", log_level = "error");
         let options = Options::parse.parse2(tokens).unwrap();
-        assert_eq!(options.file_path, "path/to/fsm.puml");
+        assert_file_path(&options, "path/to/fsm.puml");
         assert_eq!(options.codegen.log_level, Some(log::Level::Error));
     }
 
@@ -187,4 +459,194 @@ mod test {
         let result = Options::parse.parse2(tokens);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn parse_log_target_and_format() {
+        let tokens = quote::quote!(
+            file_path = "path/to/fsm.puml",
+            log_level = "info",
+            log_target = "fsm",
+            log_format = "[{fsm}] {message}"
+        );
+        let options = Options::parse.parse2(tokens).unwrap();
+        assert_eq!(options.codegen.log_target.as_deref(), Some("fsm"));
+        assert_eq!(
+            options.codegen.log_format.as_deref(),
+            Some("[{fsm}] {message}")
+        );
+    }
+
+    #[test]
+    fn parse_defmt_logger() {
+        let tokens = quote::quote!(
+            file_path = "path/to/fsm.puml",
+            log_level = "debug",
+            logger = "defmt"
+        );
+        let options = Options::parse.parse2(tokens).unwrap();
+        assert_eq!(options.codegen.logger, codegen::Logger::Defmt);
+
+        let tokens = quote::quote!(
+            file_path = "path/to/fsm.puml",
+            log_level = "debug",
+            logger = "defmt",
+            log_target = "fsm"
+        );
+        assert!(Options::parse.parse2(tokens).is_err());
+    }
+
+    #[test]
+    fn error_on_log_format_without_message() {
+        let tokens = quote::quote!(
+            file_path = "path/to/fsm.puml",
+            log_level = "info",
+            log_format = "{fsm}"
+        );
+        let result = Options::parse.parse2(tokens);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn error_on_log_target_without_log_level() {
+        let tokens = quote::quote!(file_path = "path/to/fsm.puml", log_target = "fsm");
+        let result = Options::parse.parse2(tokens);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn parse_diagram() {
+        let tokens = quote::quote!(file_path = "path/to/fsm.puml", diagram = "Door");
+        let options = Options::parse.parse2(tokens).unwrap();
+        assert_eq!(options.diagram.as_deref(), Some("Door"));
+    }
+
+    #[test]
+    fn error_on_empty_diagram() {
+        let tokens = quote::quote!(file_path = "path/to/fsm.puml", diagram = " ");
+        let result = Options::parse.parse2(tokens);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn parse_fence() {
+        let tokens = quote::quote!(file_path = "docs/design.md", fence = "Door");
+        let options = Options::parse.parse2(tokens).unwrap();
+        assert_eq!(options.fence.as_deref(), Some("Door"));
+    }
+
+    #[test]
+    fn parse_format() {
+        let tokens = quote::quote!(file_path = "path/to/fsm.md", format = "mermaid");
+        let options = Options::parse.parse2(tokens).unwrap();
+        assert_eq!(options.format, Some(Format::Mermaid));
+    }
+
+    #[test]
+    fn error_on_invalid_format() {
+        let tokens = quote::quote!(file_path = "path/to/fsm.puml", format = "graphviz");
+        let result = Options::parse.parse2(tokens);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn parse_fallible_actions() {
+        let tokens = quote::quote!(file_path = "path/to/fsm.puml", fallible_actions = true);
+        let options = Options::parse.parse2(tokens).unwrap();
+        assert!(options.codegen.fallible_actions);
+
+        let tokens = quote::quote!(file_path = "path/to/fsm.puml", fallible_actions = "yes");
+        assert!(Options::parse.parse2(tokens).is_err());
+    }
+
+    #[test]
+    fn parse_async_actions() {
+        let tokens = quote::quote!(file_path = "path/to/fsm.puml", async_actions = true);
+        let options = Options::parse.parse2(tokens).unwrap();
+        assert!(options.codegen.async_actions);
+        assert!(!options.codegen.fallible_actions);
+    }
+
+    #[test]
+    fn parse_deferred_capacity() {
+        let tokens = quote::quote!(file_path = "path/to/fsm.puml", deferred_capacity = 8);
+        let options = Options::parse.parse2(tokens).unwrap();
+        assert_eq!(options.codegen.deferred_capacity, Some(8));
+
+        let tokens = quote::quote!(file_path = "path/to/fsm.puml", deferred_capacity = 0);
+        assert!(Options::parse.parse2(tokens).is_err());
+    }
+
+    #[test]
+    fn parse_posted_capacity() {
+        let tokens = quote::quote!(file_path = "path/to/fsm.puml", posted_capacity = 4);
+        let options = Options::parse.parse2(tokens).unwrap();
+        assert_eq!(options.codegen.posted_capacity, Some(4));
+
+        let tokens = quote::quote!(file_path = "path/to/fsm.puml", posted_capacity = 0);
+        assert!(Options::parse.parse2(tokens).is_err());
+    }
+
+    #[test]
+    fn parse_tracing() {
+        let tokens = quote::quote!(file_path = "path/to/fsm.puml", tracing = "debug");
+        let options = Options::parse.parse2(tokens).unwrap();
+        assert_eq!(options.codegen.tracing_level, Some(log::Level::Debug));
+        assert_eq!(options.codegen.log_level, None);
+
+        let tokens = quote::quote!(file_path = "path/to/fsm.puml", tracing = "verbose");
+        assert!(Options::parse.parse2(tokens).is_err());
+    }
+
+    #[test]
+    fn parse_serde() {
+        let tokens = quote::quote!(file_path = "path/to/fsm.puml", serde = true);
+        let options = Options::parse.parse2(tokens).unwrap();
+        assert!(options.codegen.serde);
+
+        let tokens = quote::quote!(file_path = "path/to/fsm.puml");
+        let options = Options::parse.parse2(tokens).unwrap();
+        assert!(!options.codegen.serde);
+    }
+
+    #[test]
+    fn parse_bare_raw_string_as_source() {
+        let tokens = quote::quote!(
+            r#"@startuml Foo
+@enduml"#
+        );
+        let options = Options::parse.parse2(tokens).unwrap();
+        assert_inline_source(&options, "@startuml Foo\n@enduml");
+    }
+
+    #[test]
+    fn parse_source_as_key_value() {
+        let tokens = quote::quote!(source = "@startuml Foo\n@enduml", log_level = "info");
+        let options = Options::parse.parse2(tokens).unwrap();
+        assert_inline_source(&options, "@startuml Foo\n@enduml");
+        assert_eq!(options.codegen.log_level, Some(log::Level::Info));
+    }
+
+    #[test]
+    fn error_on_file_path_and_source() {
+        let tokens = quote::quote!(file_path = "path/to/fsm.puml", source = "@startuml Foo");
+        let result = Options::parse.parse2(tokens);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn parse_dir_with_glob() {
+        let tokens = quote::quote!(dir = "fsm/", glob = "**/*.puml");
+        let options = Options::parse.parse2(tokens).unwrap();
+        assert!(matches!(&options.source, Source::Dir(dir) if dir == "fsm/"));
+        assert_eq!(options.glob.as_deref(), Some("**/*.puml"));
+    }
+
+    #[test]
+    fn error_on_glob_without_dir() {
+        let tokens = quote::quote!(file_path = "path/to/fsm.puml", glob = "*.puml");
+        assert!(Options::parse.parse2(tokens).is_err());
+
+        let tokens = quote::quote!(dir = "fsm/", glob = "[*.puml");
+        assert!(Options::parse.parse2(tokens).is_err());
+    }
 }
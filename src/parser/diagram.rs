@@ -0,0 +1,112 @@
+/// Format independent representation of a parsed state diagram
+#[derive(Debug, PartialEq)]
+pub struct StateDiagram<'a> {
+    name: Option<&'a str>,
+    root: StateElements<'a>,
+}
+
+impl<'a> StateDiagram<'a> {
+    pub fn new(name: Option<&'a str>, root: StateElements<'a>) -> Self {
+        Self { name, root }
+    }
+
+    pub fn name(&self) -> Option<&'a str> {
+        self.name
+    }
+
+    pub fn elements(&self) -> &StateElements<'a> {
+        &self.root
+    }
+}
+
+pub type StateName<'a> = &'a str;
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct StateDescription<'a> {
+    pub name: &'a str,
+    pub description: &'a str,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct TransitionDescription<'a> {
+    pub source: StateName<'a>,
+    pub target: StateName<'a>,
+    pub description: Option<&'a str>,
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct CompositeState<'a> {
+    pub name: StateName<'a>,
+    pub elements: StateElements<'a>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum StateElement<'a> {
+    EnterState(StateName<'a>),
+    StateDeclaration(StateName<'a>),
+    Transition(TransitionDescription<'a>),
+    CompositeState(CompositeState<'a>),
+    StateDescription(StateDescription<'a>),
+}
+
+/// The elements of a scope, in the order they appear in the diagram
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct StateElements<'a>(Vec<StateElement<'a>>);
+
+impl<'a> StateElements<'a> {
+    pub fn new(elements: Vec<StateElement<'a>>) -> Self {
+        Self(elements)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &StateElement<'a>> {
+        self.0.iter()
+    }
+}
+
+#[cfg(test)]
+impl<'a> StateElements<'a> {
+    pub fn enter_states(&self) -> Vec<StateName<'a>> {
+        self.iter()
+            .filter_map(|e| match e {
+                StateElement::EnterState(name) => Some(*name),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn state_declarations(&self) -> Vec<StateName<'a>> {
+        self.iter()
+            .filter_map(|e| match e {
+                StateElement::StateDeclaration(name) => Some(*name),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn transitions(&self) -> Vec<&TransitionDescription<'a>> {
+        self.iter()
+            .filter_map(|e| match e {
+                StateElement::Transition(t) => Some(t),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn composite_states(&self) -> Vec<&CompositeState<'a>> {
+        self.iter()
+            .filter_map(|e| match e {
+                StateElement::CompositeState(c) => Some(c),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn state_descriptions(&self) -> Vec<&StateDescription<'a>> {
+        self.iter()
+            .filter_map(|e| match e {
+                StateElement::StateDescription(d) => Some(d),
+                _ => None,
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,337 @@
+use pest::Parser;
+use pest_derive::Parser;
+
+use super::diagram::{
+    CompositeState, StateDescription, StateDiagram, StateElement, StateElements, StateName,
+    TransitionDescription,
+};
+use crate::error::{Error, Position, Result};
+
+type Pair<'a> = pest::iterators::Pair<'a, Rule>;
+
+#[derive(Parser)]
+#[grammar = "parser/mermaid.pest"]
+struct MermaidParser;
+
+/// Parses a Mermaid state diagram, named by the title of its front matter
+pub fn parse(input: &str) -> Result<StateDiagram<'_>> {
+    let mut pairs = MermaidParser::parse(Rule::diagram, input).map_err(Error::from)?;
+
+    let diagram_pair = pairs
+        .next()
+        .ok_or_else(|| Error::Parse("Empty input".to_string()))?;
+
+    let mut name = None;
+    let mut root = StateElements::default();
+
+    for inner in diagram_pair.into_inner() {
+        match inner.as_rule() {
+            Rule::front_matter => name = parse_title(inner),
+            Rule::content => {
+                root = parse_content(inner)?;
+            }
+            _ => {}
+        }
+    }
+
+    let name = name.ok_or_else(|| {
+        Error::Parse("Mermaid diagram requires a `title:` in its front matter".to_string())
+    })?;
+    Ok(StateDiagram::new(Some(name), root))
+}
+
+fn parse_title(pair: Pair<'_>) -> Option<&str> {
+    pair.into_inner()
+        .find(|p| p.as_rule() == Rule::title)
+        .and_then(|p| p.into_inner().find(|p| p.as_rule() == Rule::diagram_name))
+        .map(|p| p.as_str())
+}
+
+fn parse_content(pair: Pair<'_>) -> Result<StateElements<'_>> {
+    let mut elements = Vec::new();
+
+    for element in pair.into_inner() {
+        if element.as_rule() != Rule::element {
+            continue;
+        }
+
+        for element_inner in element.into_inner() {
+            match element_inner.as_rule() {
+                Rule::enter_transition => {
+                    elements.push(StateElement::EnterState(find_state_ref(element_inner)?));
+                }
+                Rule::transition => {
+                    elements.push(StateElement::Transition(parse_transition(element_inner)?));
+                }
+                Rule::composite_state => {
+                    let composite = parse_composite_state(element_inner)?;
+                    elements.push(StateElement::CompositeState(composite));
+                }
+                Rule::pseudo_state => {
+                    elements.push(StateElement::StateDeclaration(parse_pseudo_state(
+                        element_inner,
+                    )?));
+                }
+                Rule::state_alias | Rule::state_declaration => {
+                    elements.push(StateElement::StateDeclaration(find_state_name(
+                        element_inner,
+                    )?));
+                }
+                Rule::state_description => {
+                    let description = parse_state_description(element_inner)?;
+                    elements.push(StateElement::StateDescription(description));
+                }
+                Rule::final_transition => {
+                    return Err(unsupported(
+                        &element_inner,
+                        "Final states are not supported",
+                    ));
+                }
+                Rule::concurrency => {
+                    return Err(unsupported(
+                        &element_inner,
+                        "Concurrent regions are not supported",
+                    ));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    Ok(StateElements::new(elements))
+}
+
+fn unsupported(pair: &Pair<'_>, reason: &str) -> Error {
+    let (line, column) = pair.line_col();
+    Error::Syntax(
+        format!("{reason}, found '{}' in line {line}", pair.as_str().trim()),
+        Position { line, column },
+    )
+}
+
+fn find_state_ref(pair: Pair<'_>) -> Result<StateName<'_>> {
+    pair.into_inner()
+        .find(|p| p.as_rule() == Rule::state_ref)
+        .map(|p| p.as_str())
+        .ok_or_else(|| Error::Parse("Missing state name".to_string()))
+}
+
+fn find_state_name(pair: Pair<'_>) -> Result<StateName<'_>> {
+    pair.into_inner()
+        .find(|p| p.as_rule() == Rule::state_name)
+        .map(|p| p.as_str())
+        .ok_or_else(|| Error::Parse("Missing state name".to_string()))
+}
+
+/// A choice is a simple state left by its direct transitions, forks and joins would require
+/// concurrent regions.
+fn parse_pseudo_state(pair: Pair<'_>) -> Result<StateName<'_>> {
+    let stereotype = pair
+        .clone()
+        .into_inner()
+        .find(|p| p.as_rule() == Rule::stereotype)
+        .map(|p| p.as_str());
+    if stereotype != Some("choice") {
+        return Err(unsupported(
+            &pair,
+            "Fork and join states are not supported, they require orthogonal regions",
+        ));
+    }
+    find_state_name(pair)
+}
+
+fn parse_transition(pair: Pair<'_>) -> Result<TransitionDescription<'_>> {
+    let mut states = Vec::new();
+    let mut description = None;
+
+    for inner in pair.into_inner() {
+        match inner.as_rule() {
+            Rule::state_ref => states.push(inner.as_str()),
+            Rule::description => {
+                let text = inner.as_str().trim();
+                if !text.is_empty() {
+                    description = Some(text);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    match states[..] {
+        [source, target] => Ok(TransitionDescription {
+            source,
+            target,
+            description,
+        }),
+        _ => Err(Error::Parse(
+            "Expected source and destination state in transition".to_string(),
+        )),
+    }
+}
+
+fn parse_composite_state(pair: Pair<'_>) -> Result<CompositeState<'_>> {
+    let mut name = None;
+    let mut elements = StateElements::default();
+
+    for inner in pair.into_inner() {
+        match inner.as_rule() {
+            Rule::state_name => {
+                name = Some(inner.as_str());
+            }
+            Rule::content => {
+                elements = parse_content(inner)?;
+            }
+            _ => {}
+        }
+    }
+
+    Ok(CompositeState {
+        name: name.ok_or_else(|| Error::Parse("Missing name in composite state".to_string()))?,
+        elements,
+    })
+}
+
+fn parse_state_description(pair: Pair<'_>) -> Result<StateDescription<'_>> {
+    let mut name = None;
+    let mut description = None;
+
+    for inner in pair.into_inner() {
+        match inner.as_rule() {
+            Rule::state_ref => name = Some(inner.as_str()),
+            Rule::description => description = Some(inner.as_str()),
+            _ => {}
+        }
+    }
+
+    Ok(StateDescription {
+        name: name
+            .ok_or_else(|| Error::Parse("Missing state name in state description".to_string()))?,
+        description: description
+            .ok_or_else(|| Error::Parse("Missing description in state description".to_string()))?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn titled(input: &str) -> String {
+        format!("---\ntitle: Test\n---\n{input}")
+    }
+
+    #[test]
+    fn parse_title_from_front_matter() {
+        let input = "---\ntitle: Door\nconfig:\n  theme: dark\n---\nstateDiagram-v2\n";
+        let diagram = parse(input).unwrap();
+        assert_eq!(diagram.name(), Some("Door"));
+    }
+
+    #[test]
+    fn error_without_title() {
+        let inputs = [
+            "stateDiagram\n[*] --> A",
+            "---\nconfig:\n  theme: dark\n---\nstateDiagram-v2\n[*] --> A",
+        ];
+        for input in inputs {
+            let error = parse(input).unwrap_err();
+            assert!(error.to_string().contains("requires a `title:`"), "{error}");
+        }
+    }
+
+    #[test]
+    fn parse_transitions() {
+        let input = titled(
+            r#"
+        stateDiagram-v2
+            [*] --> A
+            A --> B : Go [Allowed] / DoIt
+            B-->A
+        "#,
+        );
+        let diagram = parse(&input).unwrap();
+        let transitions = diagram.elements().transitions();
+        assert_eq!(transitions.len(), 2);
+        assert_eq!((transitions[0].source, transitions[0].target), ("A", "B"));
+        assert_eq!(transitions[0].description, Some("Go [Allowed] / DoIt"));
+        assert_eq!((transitions[1].source, transitions[1].target), ("B", "A"));
+        assert_eq!(transitions[1].description, None);
+    }
+
+    #[test]
+    fn parse_state_declarations() {
+        let input = titled(
+            r#"
+        stateDiagram-v2
+            A
+            state B
+            state "Some text" as C
+            state D <<choice>>
+            A : entry / Foo
+        "#,
+        );
+        let diagram = parse(&input).unwrap();
+        assert_eq!(
+            diagram.elements().state_declarations(),
+            vec!["A", "B", "C", "D"]
+        );
+        let descriptions = diagram.elements().state_descriptions();
+        assert_eq!(
+            (descriptions[0].name, descriptions[0].description),
+            ("A", "entry / Foo")
+        );
+    }
+
+    #[test]
+    fn parse_composite_states() {
+        let input = titled(
+            r#"
+        stateDiagram-v2
+            state Outer {
+                [*] --> Inner
+                state "Nested one" as Nested {
+                    [*] --> Deep
+                }
+            }
+        "#,
+        );
+        let diagram = parse(&input).unwrap();
+        let outer = diagram.elements().composite_states()[0];
+        assert_eq!(outer.name, "Outer");
+        assert_eq!(outer.elements.enter_states(), vec!["Inner"]);
+        assert_eq!(outer.elements.composite_states()[0].name, "Nested");
+    }
+
+    #[test]
+    fn ignore_notes_comments_and_styling() {
+        let input = titled(
+            r#"
+        stateDiagram-v2
+            direction LR
+            %% A comment
+            [*] --> A
+            note right of A : A note
+            note left of A
+                A note --> spanning
+                several lines
+            end note
+            classDef important font-weight:bold
+            class A important
+        "#,
+        );
+        let diagram = parse(&input).unwrap();
+        assert_eq!(diagram.elements().iter().count(), 1);
+    }
+
+    #[test]
+    fn error_on_unsupported_elements() {
+        let unsupported = [
+            "stateDiagram-v2\nA --> [*]\n",
+            "stateDiagram-v2\nstate F <<fork>>\n",
+            "stateDiagram-v2\nstate J <<join>>\n",
+            "stateDiagram-v2\nstate C {\nA\n--\nB\n}\n",
+        ];
+        for input in unsupported {
+            assert!(parse(&titled(input)).is_err(), "{input}");
+        }
+    }
+}
@@ -1,25 +1,139 @@
 use crate::error::{Error, Result};
 use crate::fsm::{StateId, StateType, TransitionParameters, UmlFsm, UmlFsmBuilder};
 
+mod diagram;
+mod mermaid;
 mod plantuml;
+mod scxml;
 mod uml;
 
+use itertools::Itertools;
 use log::trace;
 
+/// Input format of a diagram
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    #[default]
+    PlantUml,
+    Mermaid,
+    Scxml,
+}
+
+impl Format {
+    /// Mermaid for `.mmd`, SCXML for `.scxml` files, PlantUML otherwise
+    pub fn from_extension(extension: Option<&str>) -> Self {
+        match extension {
+            Some("mmd") => Format::Mermaid,
+            Some("scxml") => Format::Scxml,
+            _ => Format::PlantUml,
+        }
+    }
+
+    /// Pattern of the files of the format within a directory
+    pub fn glob(self) -> &'static str {
+        match self {
+            Format::PlantUml => "*.puml",
+            Format::Mermaid => "*.mmd",
+            Format::Scxml => "*.scxml",
+        }
+    }
+}
+
 impl UmlFsm {
-    pub fn try_parse<C>(content: C) -> Result<UmlFsm>
+    /// Parses the only diagram of the content
+    pub fn try_parse<C>(content: C, format: Format) -> Result<UmlFsm>
+    where
+        C: AsRef<str>,
+    {
+        if format == Format::Scxml {
+            return scxml::parse(content.as_ref());
+        }
+        let mut diagrams = parse_diagrams(content.as_ref(), format)?;
+        if diagrams.len() > 1 {
+            return Err(Error::Parse(format!(
+                "Found {} diagrams ({}), select one with the 'diagram' option",
+                diagrams.len(),
+                diagram_names(&diagrams)
+            )));
+        }
+        diagrams.remove(0).try_into()
+    }
+
+    /// Parses the diagram with the given name
+    pub fn try_parse_diagram<C>(content: C, format: Format, name: &str) -> Result<UmlFsm>
+    where
+        C: AsRef<str>,
+    {
+        if format == Format::Scxml {
+            return scxml::parse(content.as_ref()).and_then(|fsm| {
+                if fsm.name() == name {
+                    Ok(fsm)
+                } else {
+                    Err(Error::Parse(format!(
+                        "Diagram '{name}' not found, available diagrams are: {}",
+                        fsm.name()
+                    )))
+                }
+            });
+        }
+        let diagrams = parse_diagrams(content.as_ref(), format)?;
+        let names = diagram_names(&diagrams);
+        diagrams
+            .into_iter()
+            .find(|d| d.name() == Some(name))
+            .ok_or_else(|| {
+                Error::Parse(format!(
+                    "Diagram '{name}' not found, available diagrams are: {names}"
+                ))
+            })?
+            .try_into()
+    }
+
+    /// Parses all diagrams of the content, which must have distinct names
+    pub fn try_parse_all<C>(content: C, format: Format) -> Result<Vec<UmlFsm>>
     where
         C: AsRef<str>,
     {
-        let diagram = plantuml::StateDiagram::parse(content.as_ref())?;
-        trace!("Parsed PlantUML diagram: {:#?}", diagram);
-        diagram.try_into()
+        if format == Format::Scxml {
+            return Ok(vec![scxml::parse(content.as_ref())?]);
+        }
+        let diagrams = parse_diagrams(content.as_ref(), format)?;
+        match diagrams.iter().map(|d| d.name()).duplicates().next() {
+            Some(Some(name)) => {
+                return Err(Error::Parse(format!("Duplicate diagram name '{name}'")));
+            }
+            Some(None) => {
+                return Err(Error::Parse(
+                    "Found several diagrams without a name, name each of them, e.g. '@startuml Name'"
+                        .to_string(),
+                ));
+            }
+            None => {}
+        }
+        diagrams.into_iter().map(UmlFsm::try_from).collect()
     }
 }
 
-impl TryFrom<plantuml::StateDiagram<'_>> for UmlFsm {
+fn parse_diagrams(content: &str, format: Format) -> Result<Vec<diagram::StateDiagram<'_>>> {
+    let diagrams = match format {
+        Format::PlantUml => plantuml::parse_all(content)?,
+        Format::Mermaid => vec![mermaid::parse(content)?],
+        Format::Scxml => unreachable!("SCXML is read into an FSM directly"),
+    };
+    trace!("Parsed {:?} diagrams: {:#?}", format, diagrams);
+    Ok(diagrams)
+}
+
+fn diagram_names(diagrams: &[diagram::StateDiagram<'_>]) -> String {
+    diagrams
+        .iter()
+        .map(|d| d.name().unwrap_or("<unnamed>"))
+        .join(", ")
+}
+
+impl TryFrom<diagram::StateDiagram<'_>> for UmlFsm {
     type Error = Error;
-    fn try_from(diagram: plantuml::StateDiagram<'_>) -> Result<Self> {
+    fn try_from(diagram: diagram::StateDiagram<'_>) -> Result<Self> {
         let name = diagram.name().map(|s| s.to_string()).unwrap_or_default();
         let mut builder = UmlFsmBuilder::new(name);
 
@@ -29,61 +143,33 @@ impl TryFrom<plantuml::StateDiagram<'_>> for UmlFsm {
     }
 }
 
-// TODO order matters here. there might be a mismatch on how plantuml processes this (line by line
-// vs element by element), need to verify
+/// Adds the elements of a scope in diagram order. States are declared right away, whereas
+/// elements referring to states by name are resolved by the builder once all states are known.
 fn add_fsm_elements(
     builder: &mut UmlFsmBuilder,
-    elements: &plantuml::StateElements<'_>,
+    elements: &diagram::StateElements<'_>,
     scope: Option<StateId>,
 ) -> Result<()> {
     let previous_scope = builder.set_scope(scope);
 
-    for composite in &elements.composite_states {
-        let state = builder.add_state(composite.name, StateType::Simple);
-        add_fsm_elements(builder, &composite.elements, Some(state))?;
-    }
-
-    for enter_state in &elements.enter_states {
-        builder.add_state(enter_state, StateType::Enter);
-    }
-    // Add transitions last, as they can create new states
-    for transition in &elements.transitions {
-        let (event, action, guard) = if let Some(desc) = transition.description {
-            let label = uml::TransitionLabel::try_from(desc)?;
-            (label.event, label.action, label.guard)
-        } else {
-            (None, None, None)
-        };
-        builder.add_transition(TransitionParameters {
-            source: transition.source,
-            target: Some(transition.target),
-            event,
-            action,
-            guard,
-        });
-    }
-
-    for desc in &elements.state_descriptions {
-        match uml::StateDescription::try_from(desc.description) {
-            Ok(uml::StateDescription::Entry(action)) => {
-                builder.add_enter_action(desc.name, action);
+    for element in elements.iter() {
+        match element {
+            diagram::StateElement::CompositeState(composite) => {
+                let state = builder.add_state(composite.name, StateType::Simple);
+                add_fsm_elements(builder, &composite.elements, Some(state))?;
+            }
+            diagram::StateElement::EnterState(state) => {
+                builder.add_enter_state(state);
             }
-            Ok(uml::StateDescription::Exit(action)) => {
-                builder.add_exit_action(desc.name, action);
+            diagram::StateElement::StateDeclaration(state) => {
+                builder.add_state(state, StateType::Simple);
             }
-            Ok(uml::StateDescription::DeferEvent(event)) => {
-                builder.add_deferred_event(desc.name, event);
+            diagram::StateElement::Transition(transition) => {
+                builder.add_transition(transition.clone().try_into()?);
             }
-            Ok(uml::StateDescription::InternalTransition(label)) => {
-                builder.add_transition(TransitionParameters {
-                    source: desc.name,
-                    target: None,
-                    event: label.event,
-                    action: label.action,
-                    guard: label.guard,
-                });
+            diagram::StateElement::StateDescription(desc) => {
+                add_state_description(builder, desc);
             }
-            Err(_) => {} // unrecognised description, skip
         }
     }
 
@@ -91,9 +177,33 @@ fn add_fsm_elements(
     Ok(())
 }
 
-impl<'a> TryFrom<plantuml::TransitionDescription<'a>> for TransitionParameters<'a> {
+fn add_state_description(builder: &mut UmlFsmBuilder, desc: &diagram::StateDescription<'_>) {
+    match uml::StateDescription::try_from(desc.description) {
+        Ok(uml::StateDescription::Entry(action)) => {
+            builder.add_enter_action(desc.name, action);
+        }
+        Ok(uml::StateDescription::Exit(action)) => {
+            builder.add_exit_action(desc.name, action);
+        }
+        Ok(uml::StateDescription::DeferEvent(event)) => {
+            builder.add_deferred_event(desc.name, event);
+        }
+        Ok(uml::StateDescription::InternalTransition(label)) => {
+            builder.add_transition(TransitionParameters {
+                source: desc.name,
+                target: None,
+                event: label.event,
+                action: label.action,
+                guard: label.guard,
+            });
+        }
+        Err(_) => {} // unrecognised description, skip
+    }
+}
+
+impl<'a> TryFrom<diagram::TransitionDescription<'a>> for TransitionParameters<'a> {
     type Error = crate::error::Error;
-    fn try_from(transition: plantuml::TransitionDescription<'a>) -> Result<Self> {
+    fn try_from(transition: diagram::TransitionDescription<'a>) -> Result<Self> {
         let (event, action, guard) = if let Some(desc) = transition.description {
             let label = uml::TransitionLabel::try_from(desc)?;
             (label.event, label.action, label.guard)
@@ -112,16 +222,72 @@ impl<'a> TryFrom<plantuml::TransitionDescription<'a>> for TransitionParameters<'
 
 #[cfg(test)]
 mod test {
-    use crate::{fsm::UmlFsm, test::FsmTestData};
+    use super::Format;
+    use crate::{error::Error, fsm::UmlFsm, test::FsmTestData};
     use pretty_assertions::assert_eq;
     use test_casing::{TestCases, cases, test_casing};
 
     const FSM_CASES: TestCases<FsmTestData> = cases!(FsmTestData::all());
 
-    #[test_casing(12, FSM_CASES)]
+    #[test_casing(15, FSM_CASES)]
     fn parses_fsm(data: FsmTestData) {
         crate::logging::init();
-        let fsm = UmlFsm::try_parse(data.content).unwrap();
+        let fsm = UmlFsm::try_parse(data.content, data.format()).unwrap();
         assert_eq!(data.parsed, fsm);
     }
+
+    const MULTIPLE_DIAGRAMS: &str = include_str!("../test/misc/multiple_diagrams.puml");
+
+    #[test]
+    fn parses_selected_diagram() {
+        let fsm = UmlFsm::try_parse_diagram(MULTIPLE_DIAGRAMS, Format::PlantUml, "Light").unwrap();
+        assert_eq!(fsm.name(), "Light");
+    }
+
+    #[test]
+    fn error_on_unknown_diagram() {
+        let result = UmlFsm::try_parse_diagram(MULTIPLE_DIAGRAMS, Format::PlantUml, "Window");
+        assert_eq!(
+            result.unwrap_err(),
+            Error::Parse(
+                "Diagram 'Window' not found, available diagrams are: Door, Light".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn error_on_ambiguous_diagram() {
+        let result = UmlFsm::try_parse(MULTIPLE_DIAGRAMS, Format::PlantUml);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn parses_all_diagrams() {
+        let fsms = UmlFsm::try_parse_all(MULTIPLE_DIAGRAMS, Format::PlantUml).unwrap();
+        let names: Vec<_> = fsms.iter().map(|f| f.name()).collect();
+        assert_eq!(names, vec!["Door", "Light"]);
+    }
+
+    #[test]
+    fn error_on_duplicate_diagram_names() {
+        let content = "@startuml A\n[*] --> X\n@enduml\n@startuml A\n[*] --> Y\n@enduml\n";
+        let result = UmlFsm::try_parse_all(content, Format::PlantUml);
+        assert_eq!(
+            result.unwrap_err(),
+            Error::Parse("Duplicate diagram name 'A'".to_string())
+        );
+    }
+
+    #[test]
+    fn error_on_several_unnamed_diagrams() {
+        let content = "@startuml\n[*] --> X\n@enduml\n@startuml\n[*] --> Y\n@enduml\n";
+        let result = UmlFsm::try_parse_all(content, Format::PlantUml);
+        assert_eq!(
+            result.unwrap_err(),
+            Error::Parse(
+                "Found several diagrams without a name, name each of them, e.g. '@startuml Name'"
+                    .to_string()
+            )
+        );
+    }
 }
@@ -1,82 +1,50 @@
 use pest::Parser;
 use pest_derive::Parser;
 
+use super::diagram::{
+    CompositeState, StateDescription, StateDiagram, StateElement, StateElements, StateName,
+    TransitionDescription,
+};
 use crate::error::{Error, Result};
 
-#[derive(Debug, PartialEq)]
-pub struct StateDiagram<'a> {
-    name: Option<&'a str>,
-    root: StateElements<'a>,
-}
-
-pub type StateName<'a> = &'a str;
-
-#[derive(Debug, PartialEq, Clone, Default)]
-pub struct StateDescription<'a> {
-    pub name: &'a str,
-    pub description: &'a str,
-}
-
-#[derive(Debug, PartialEq, Clone)]
-pub struct TransitionDescription<'a> {
-    pub source: StateName<'a>,
-    pub target: StateName<'a>,
-    pub description: Option<&'a str>,
-}
-
-#[derive(Debug, PartialEq, Clone, Default)]
-pub struct CompositeState<'a> {
-    pub name: StateName<'a>,
-    pub elements: StateElements<'a>,
-}
-
-#[derive(Debug, PartialEq, Clone, Default)]
-pub struct StateElements<'a> {
-    pub enter_states: Vec<StateName<'a>>,
-    pub transitions: Vec<TransitionDescription<'a>>,
-    pub composite_states: Vec<CompositeState<'a>>,
-    pub state_descriptions: Vec<StateDescription<'a>>,
-}
-
 type Pair<'a> = pest::iterators::Pair<'a, Rule>;
 
 #[derive(Parser)]
 #[grammar = "parser/plantuml.pest"]
 struct PlantUmlParser;
 
-impl StateDiagram<'_> {
-    pub fn parse(input: &str) -> Result<StateDiagram<'_>> {
-        let mut pairs =
-            PlantUmlParser::parse(Rule::diagram, input).map_err(|e| Error::Parse(e.to_string()))?;
+/// Parses all diagrams of the input, in the order they appear
+pub fn parse_all(input: &str) -> Result<Vec<StateDiagram<'_>>> {
+    let mut pairs = PlantUmlParser::parse(Rule::diagrams, input).map_err(Error::from)?;
 
-        let diagram_pair = pairs
-            .next()
-            .ok_or_else(|| Error::Parse("Empty input".to_string()))?;
+    let diagrams_pair = pairs
+        .next()
+        .ok_or_else(|| Error::Parse("Empty input".to_string()))?;
 
-        let mut name = None;
-        let mut root = StateElements::default();
+    diagrams_pair
+        .into_inner()
+        .filter(|p| p.as_rule() == Rule::diagram)
+        .map(parse_diagram)
+        .collect()
+}
 
-        for inner in diagram_pair.into_inner() {
-            match inner.as_rule() {
-                Rule::startuml => name = parse_diagram_name(inner),
-                Rule::content => {
-                    root = parse_content(inner)?;
-                }
-                _ => {}
+fn parse_diagram(pair: Pair<'_>) -> Result<StateDiagram<'_>> {
+    let mut name = None;
+    let mut root = StateElements::default();
+
+    for inner in pair.into_inner() {
+        match inner.as_rule() {
+            Rule::startuml => name = parse_diagram_name(inner),
+            Rule::content => {
+                root = parse_content(inner)?;
             }
+            _ => {}
         }
-
-        Ok(StateDiagram { name, root })
-    }
-
-    pub fn name(&self) -> Option<&str> {
-        self.name
     }
 
-    pub fn elements(&self) -> &StateElements<'_> {
-        &self.root
-    }
+    Ok(StateDiagram::new(name, root))
 }
+
 fn parse_diagram_name(pair: Pair<'_>) -> Option<&str> {
     pair.into_inner()
         .find(|p| p.as_rule() == Rule::diagram_name)
@@ -84,10 +52,7 @@ fn parse_diagram_name(pair: Pair<'_>) -> Option<&str> {
 }
 
 fn parse_content(pair: Pair<'_>) -> Result<StateElements<'_>> {
-    let mut enter_states = Vec::new();
-    let mut transitions = Vec::new();
-    let mut composite_states = Vec::new();
-    let mut state_descriptions = Vec::new();
+    let mut elements = Vec::new();
 
     for element in pair.into_inner() {
         if element.as_rule() != Rule::element {
@@ -98,32 +63,46 @@ fn parse_content(pair: Pair<'_>) -> Result<StateElements<'_>> {
             match element_inner.as_rule() {
                 Rule::enter_transition => {
                     if let Some(state) = parse_enter_state(element_inner) {
-                        enter_states.push(state);
+                        elements.push(StateElement::EnterState(state));
                     }
                 }
                 Rule::transition => {
-                    transitions.push(parse_transition(element_inner)?);
+                    let transition = parse_transition(element_inner)?;
+                    elements.push(StateElement::Transition(transition));
                 }
                 Rule::composite_state => {
-                    composite_states.push(parse_composite_state(element_inner)?);
+                    let composite = parse_composite_state(element_inner)?;
+                    elements.push(StateElement::CompositeState(composite));
                 }
-                Rule::state_declaration_with_desc | Rule::state_description => {
-                    state_descriptions.push(parse_state_description(element_inner)?);
+                Rule::state_declaration => {
+                    if let Some(state) = parse_state_declaration(element_inner) {
+                        elements.push(StateElement::StateDeclaration(state));
+                    }
+                }
+                Rule::state_declaration_with_desc => {
+                    let description = parse_state_description(element_inner)?;
+                    elements.push(StateElement::StateDeclaration(description.name));
+                    elements.push(StateElement::StateDescription(description));
+                }
+                Rule::state_description => {
+                    let description = parse_state_description(element_inner)?;
+                    elements.push(StateElement::StateDescription(description));
                 }
                 _ => {}
             }
         }
     }
 
-    Ok(StateElements {
-        enter_states,
-        transitions,
-        composite_states,
-        state_descriptions,
-    })
+    Ok(StateElements::new(elements))
 }
 
 fn parse_enter_state(pair: Pair<'_>) -> Option<StateName<'_>> {
+    pair.into_inner()
+        .find(|p| p.as_rule() == Rule::state_ref)
+        .map(|p| p.as_str())
+}
+
+fn parse_state_declaration(pair: Pair<'_>) -> Option<StateName<'_>> {
     pair.into_inner()
         .find(|p| p.as_rule() == Rule::state_name)
         .map(|p| p.as_str())
@@ -136,7 +115,7 @@ fn parse_transition(pair: Pair<'_>) -> Result<TransitionDescription<'_>> {
 
     for inner in pair.into_inner() {
         match inner.as_rule() {
-            Rule::state_name => {
+            Rule::state_ref => {
                 if from.is_none() {
                     from = Some(inner.as_str());
                 } else {
@@ -192,7 +171,7 @@ fn parse_state_description(
 
     for inner in element_inner.into_inner() {
         match inner.as_rule() {
-            Rule::state_name => {
+            Rule::state_name | Rule::state_ref => {
                 name = Some(inner.as_str());
             }
             Rule::description => {
@@ -214,6 +193,13 @@ fn parse_state_description(
 mod tests {
     use super::*;
 
+    /// Parses an input containing exactly one diagram
+    fn parse(input: &str) -> Result<StateDiagram<'_>> {
+        let mut diagrams = parse_all(input)?;
+        assert_eq!(diagrams.len(), 1, "Expected a single diagram");
+        Ok(diagrams.remove(0))
+    }
+
     impl CompositeState<'_> {
         fn assert_name(&self, expected: &str) -> &Self {
             assert_eq!(self.name, expected);
@@ -222,7 +208,7 @@ mod tests {
 
         fn assert_children(&self, expected: usize) -> &Self {
             assert_eq!(
-                self.elements.composite_states.len(),
+                self.elements.composite_states().len(),
                 expected,
                 "children count for '{}'",
                 self.name
@@ -232,7 +218,8 @@ mod tests {
 
         fn assert_enters(&self, expected: &[&str]) -> &Self {
             assert_eq!(
-                self.elements.enter_states, expected,
+                self.elements.enter_states(),
+                expected,
                 "enter_states for '{}'",
                 self.name
             );
@@ -240,7 +227,7 @@ mod tests {
         }
 
         fn assert_transition(&self, idx: usize, from: &str, to: &str) -> &Self {
-            let t = &self.elements.transitions[idx];
+            let t = &self.elements.transitions()[idx];
             assert_eq!(
                 (t.source, t.target),
                 (from, to),
@@ -252,35 +239,38 @@ mod tests {
         }
 
         fn child(&self, idx: usize) -> &CompositeState<'_> {
-            &self.elements.composite_states[idx]
+            self.elements.composite_states()[idx]
         }
     }
 
     #[test]
     fn test_parse_uml_content() {
         let full_input = "@startuml fsm name\n@enduml";
-        let diagram = StateDiagram::parse(full_input).unwrap();
-        assert_eq!(diagram.name, Some("fsm name"));
+        let diagram = parse(full_input).unwrap();
+        assert_eq!(diagram.name(), Some("fsm name"));
     }
 
     #[test]
     fn test_parse_state_description() {
         let input = "A : some description\n";
         let full_input = format!("@startuml test\n{}@enduml", input);
-        let diagram = StateDiagram::parse(&full_input).unwrap();
+        let diagram = parse(&full_input).unwrap();
         // State descriptions are parsed but not stored in transitions
-        assert_eq!(diagram.root.transitions.len(), 0);
+        assert_eq!(diagram.elements().transitions().len(), 0);
     }
 
     #[test]
     fn test_parse_transition() {
         let input = "A -> B : label\n";
         let full_input = format!("@startuml test\n{}@enduml", input);
-        let diagram = StateDiagram::parse(&full_input).unwrap();
-        assert_eq!(diagram.root.transitions.len(), 1);
-        assert_eq!(diagram.root.transitions[0].source, "A");
-        assert_eq!(diagram.root.transitions[0].target, "B");
-        assert_eq!(diagram.root.transitions[0].description, Some("label"));
+        let diagram = parse(&full_input).unwrap();
+        assert_eq!(diagram.elements().transitions().len(), 1);
+        assert_eq!(diagram.elements().transitions()[0].source, "A");
+        assert_eq!(diagram.elements().transitions()[0].target, "B");
+        assert_eq!(
+            diagram.elements().transitions()[0].description,
+            Some("label")
+        );
     }
 
     #[test]
@@ -290,24 +280,24 @@ mod tests {
         A -u-> C : label2
         "#;
         let full_input = format!("@startuml test\n{}@enduml", input);
-        let diagram = StateDiagram::parse(&full_input).unwrap();
-        assert_eq!(diagram.root.transitions.len(), 2);
+        let diagram = parse(&full_input).unwrap();
+        assert_eq!(diagram.elements().transitions().len(), 2);
     }
 
     #[test]
     fn test_parse_enter_transition() {
         let input = "[*] --> A\n";
         let full_input = format!("@startuml test\n{}@enduml", input);
-        let diagram = StateDiagram::parse(&full_input).unwrap();
-        assert_eq!(diagram.root.enter_states, vec!["A"]);
+        let diagram = parse(&full_input).unwrap();
+        assert_eq!(diagram.elements().enter_states(), vec!["A"]);
     }
 
     #[test]
     fn test_parse_exit_transition() {
         let input = "[*] --> A\n";
         let full_input = format!("@startuml test\n{}@enduml", input);
-        let diagram = StateDiagram::parse(&full_input).unwrap();
-        assert_eq!(diagram.root.enter_states, vec!["A"]);
+        let diagram = parse(&full_input).unwrap();
+        assert_eq!(diagram.elements().enter_states(), vec!["A"]);
     }
 
     #[test]
@@ -316,8 +306,8 @@ mod tests {
         @startuml fsm name
         @enduml
         "#;
-        let diagram = StateDiagram::parse(input).unwrap();
-        assert_eq!(diagram.name, Some("fsm name"));
+        let diagram = parse(input).unwrap();
+        assert_eq!(diagram.name(), Some("fsm name"));
     }
 
     #[test]
@@ -330,10 +320,10 @@ mod tests {
         B --> D : label3
         @enduml
         "#;
-        let diagram = StateDiagram::parse(input).unwrap();
-        assert_eq!(diagram.name, Some("fsm name"));
-        assert_eq!(diagram.root.enter_states, vec!["A"]);
-        assert_eq!(diagram.root.transitions.len(), 3);
+        let diagram = parse(input).unwrap();
+        assert_eq!(diagram.name(), Some("fsm name"));
+        assert_eq!(diagram.elements().enter_states(), vec!["A"]);
+        assert_eq!(diagram.elements().transitions().len(), 3);
     }
     #[test]
     fn test_parse_fsm_diagram_with_state_descriptions() {
@@ -345,10 +335,10 @@ mod tests {
         state B: some desc
         @enduml
         "#;
-        let diagram = StateDiagram::parse(input).unwrap();
-        assert_eq!(diagram.name, Some("fsm name"));
-        assert_eq!(diagram.root.enter_states, vec!["A"]);
-        assert_eq!(diagram.root.transitions.len(), 1);
+        let diagram = parse(input).unwrap();
+        assert_eq!(diagram.name(), Some("fsm name"));
+        assert_eq!(diagram.elements().enter_states(), vec!["A"]);
+        assert_eq!(diagram.elements().transitions().len(), 1);
     }
 
     #[test]
@@ -359,9 +349,9 @@ mod tests {
         StateA --> StateA : SelfTransition : Action1
         @enduml
         "#;
-        let diagram = StateDiagram::parse(input).unwrap();
-        assert_eq!(diagram.root.enter_states, vec!["StateA"]);
-        assert_eq!(diagram.root.transitions.len(), 1);
+        let diagram = parse(input).unwrap();
+        assert_eq!(diagram.elements().enter_states(), vec!["StateA"]);
+        assert_eq!(diagram.elements().transitions().len(), 1);
     }
 
     #[test]
@@ -371,9 +361,9 @@ mod tests {
         ' This is a comment
         @enduml
         "#;
-        let diagram = StateDiagram::parse(input).unwrap();
-        assert_eq!(diagram.name, Some("test"));
-        assert_eq!(diagram.root.transitions.len(), 0);
+        let diagram = parse(input).unwrap();
+        assert_eq!(diagram.name(), Some("test"));
+        assert_eq!(diagram.elements().transitions().len(), 0);
     }
 
     #[test]
@@ -389,9 +379,9 @@ mod tests {
         ' Comment at the end
         @enduml
         "#;
-        let diagram = StateDiagram::parse(input).unwrap();
-        assert_eq!(diagram.root.enter_states, vec!["A"]);
-        assert_eq!(diagram.root.transitions.len(), 2);
+        let diagram = parse(input).unwrap();
+        assert_eq!(diagram.elements().enter_states(), vec!["A"]);
+        assert_eq!(diagram.elements().transitions().len(), 2);
     }
 
     #[test]
@@ -402,8 +392,8 @@ mod tests {
         [*] --> A
         @enduml
         "#;
-        let diagram = StateDiagram::parse(input).unwrap();
-        assert_eq!(diagram.root.enter_states, vec!["A"]);
+        let diagram = parse(input).unwrap();
+        assert_eq!(diagram.elements().enter_states(), vec!["A"]);
     }
 
     #[test]
@@ -418,11 +408,11 @@ mod tests {
         }
         @enduml
         "#;
-        let fsm = StateDiagram::parse(input).expect("Failed to parse FSM");
-        assert_eq!(fsm.name, Some("CompositeFSM"));
-        assert_eq!(fsm.root.composite_states.len(), 1);
+        let fsm = parse(input).expect("Failed to parse FSM");
+        assert_eq!(fsm.name(), Some("CompositeFSM"));
+        assert_eq!(fsm.elements().composite_states().len(), 1);
 
-        fsm.root.composite_states[0]
+        fsm.elements().composite_states()[0]
             .assert_name("State1")
             .assert_children(1)
             .child(0)
@@ -448,11 +438,11 @@ mod tests {
         }
         @enduml
         "#;
-        let fsm = StateDiagram::parse(input).expect("Failed to parse FSM");
-        assert_eq!(fsm.name, Some("CompositeFSM"));
-        assert_eq!(fsm.root.composite_states.len(), 1);
+        let fsm = parse(input).expect("Failed to parse FSM");
+        assert_eq!(fsm.name(), Some("CompositeFSM"));
+        assert_eq!(fsm.elements().composite_states().len(), 1);
 
-        let state1a = fsm.root.composite_states[0]
+        let state1a = fsm.elements().composite_states()[0]
             .assert_name("State1")
             .assert_children(1)
             .child(0);
@@ -487,11 +477,11 @@ mod tests {
         }
         @enduml
         "#;
-        let fsm = StateDiagram::parse(input).expect("Failed to parse FSM");
-        assert_eq!(fsm.name, Some("CompositeFSM"));
-        assert_eq!(fsm.root.composite_states.len(), 2);
+        let fsm = parse(input).expect("Failed to parse FSM");
+        assert_eq!(fsm.name(), Some("CompositeFSM"));
+        assert_eq!(fsm.elements().composite_states().len(), 2);
 
-        fsm.root.composite_states[0]
+        fsm.elements().composite_states()[0]
             .assert_name("State1")
             .assert_children(1)
             .child(0)
@@ -501,7 +491,7 @@ mod tests {
             .assert_name("state1B")
             .assert_children(0);
 
-        fsm.root.composite_states[1]
+        fsm.elements().composite_states()[1]
             .assert_name("State2")
             .assert_children(1)
             .child(0)
@@ -536,12 +526,12 @@ mod tests {
         }
         @enduml
         "#;
-        let fsm = StateDiagram::parse(input).expect("Failed to parse FSM");
-        assert_eq!(fsm.name, Some("CompositeFSM"));
-        assert_eq!(fsm.root.composite_states.len(), 2);
+        let fsm = parse(input).expect("Failed to parse FSM");
+        assert_eq!(fsm.name(), Some("CompositeFSM"));
+        assert_eq!(fsm.elements().composite_states().len(), 2);
 
         // State1 hierarchy
-        let state1a = fsm.root.composite_states[0]
+        let state1a = fsm.elements().composite_states()[0]
             .assert_name("State1")
             .assert_children(1)
             .child(0);
@@ -559,7 +549,7 @@ mod tests {
             .assert_enters(&["State1C"]);
 
         // State2 hierarchy
-        let state2a = fsm.root.composite_states[1]
+        let state2a = fsm.elements().composite_states()[1]
             .assert_name("State2")
             .assert_children(1)
             .child(0);
@@ -577,6 +567,101 @@ mod tests {
             .assert_enters(&["State2C"]);
     }
 
+    #[test]
+    fn parse_state_declarations() {
+        let input = r#"
+        @startuml test
+        state A
+        state B : This is state B
+        C : This is state C
+        @enduml
+        "#;
+        let diagram = parse(input).unwrap();
+        assert_eq!(diagram.elements().state_declarations(), vec!["A", "B"]);
+        assert_eq!(diagram.elements().state_descriptions().len(), 2);
+    }
+
+    #[test]
+    fn parse_qualified_state_references() {
+        let input = r#"
+        @startuml test
+        [*] --> A.B
+        A.B --> C::D : label
+        C::D : entry / Foo
+        @enduml
+        "#;
+        let diagram = parse(input).unwrap();
+        assert_eq!(diagram.elements().enter_states(), vec!["A.B"]);
+        let transitions = diagram.elements().transitions();
+        assert_eq!(
+            (transitions[0].source, transitions[0].target),
+            ("A.B", "C::D")
+        );
+        assert_eq!(diagram.elements().state_descriptions()[0].name, "C::D");
+    }
+
+    #[test]
+    fn parse_unicode_underscore_and_dash_names() {
+        let input = r#"
+        @startuml test
+        [*] --> Überhitzt
+        Überhitzt --> cool_down : go-to-cool / cool_down
+        cool_down-->cool-down-2
+        cool-down-2 -> Überhitzt
+        @enduml
+        "#;
+        let diagram = parse(input).unwrap();
+        assert_eq!(diagram.elements().enter_states(), vec!["Überhitzt"]);
+        let transitions: Vec<_> = diagram
+            .elements()
+            .transitions()
+            .iter()
+            .map(|t| (t.source, t.target))
+            .collect();
+        assert_eq!(
+            transitions,
+            vec![
+                ("Überhitzt", "cool_down"),
+                ("cool_down", "cool-down-2"),
+                ("cool-down-2", "Überhitzt"),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_multiple_diagrams() {
+        let input = r#"
+        ' Comments are allowed outside of diagrams
+        @startuml First
+        [*] --> A
+        @enduml
+        ' Notes on the second diagram
+        @startuml Second
+        [*] --> B
+        @enduml
+        "#;
+        let diagrams = parse_all(input).unwrap();
+        let names: Vec<_> = diagrams.iter().map(|d| d.name()).collect();
+        assert_eq!(names, vec![Some("First"), Some("Second")]);
+        assert_eq!(diagrams[1].elements().enter_states(), vec!["B"]);
+    }
+
+    #[test]
+    fn parse_rejects_text_outside_of_diagrams() {
+        let input = r#"
+        @startuml First
+        [*] --> A
+        @enduml
+        [*] --> B
+        "#;
+        assert!(parse_all(input).is_err());
+    }
+
+    #[test]
+    fn parse_requires_a_diagram() {
+        assert!(parse_all("no diagram").is_err());
+    }
+
     #[test]
     fn parse_state_description_test() {
         let input = r#"
@@ -585,8 +670,8 @@ mod tests {
         state B : This is state B
         @enduml
         "#;
-        let diagram = StateDiagram::parse(input).unwrap();
-        let descriptions: Vec<_> = diagram.root.state_descriptions;
+        let diagram = parse(input).unwrap();
+        let descriptions: Vec<_> = diagram.elements().state_descriptions();
         assert_eq!(descriptions.len(), 2);
         assert_eq!(descriptions[0].name, "A");
         assert_eq!(descriptions[0].description, "This is state A");
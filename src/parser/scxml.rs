@@ -0,0 +1,361 @@
+use roxmltree::{Document, Node};
+
+use crate::error::{Error, Position, Result};
+use crate::fsm::{Action, Event, StateType, TransitionParameters, UmlFsm, UmlFsmBuilder};
+
+const SCXML_NAMESPACE: &str = "http://www.w3.org/2005/07/scxml";
+
+/// Reads an SCXML document, named by the required `name` attribute of its `<scxml>` element.
+///
+/// Actions are given as `<script>ActionName</script>` and guards by the name in `cond`.
+/// Elements of other namespaces, e.g. editor metadata, are ignored.
+pub fn parse(input: &str) -> Result<UmlFsm> {
+    let document = Document::parse(input).map_err(|e| {
+        let position = Position {
+            line: e.pos().row as usize,
+            column: e.pos().col as usize,
+        };
+        Error::Syntax(format!("Invalid SCXML: {e}"), position)
+    })?;
+    let root = document.root_element();
+    if root.tag_name().name() != "scxml" {
+        return Err(Error::Parse(format!(
+            "Expected <scxml> root element, found <{}>",
+            root.tag_name().name()
+        )));
+    }
+
+    let name = root
+        .attribute("name")
+        .ok_or_else(|| Error::Parse("SCXML document requires a 'name' attribute".to_string()))?;
+    let mut builder = UmlFsmBuilder::new(name);
+    add_substates(&mut builder, root)?;
+    builder.build()
+}
+
+/// Adds the child states of the `<scxml>` or a `<state>` element in the current scope.
+/// The initial state is given by the `initial` attribute or element, or else is the first
+/// child state.
+fn add_substates(builder: &mut UmlFsmBuilder, parent: Node<'_, '_>) -> Result<()> {
+    let mut initial = parent.attribute("initial");
+    let mut substates = Vec::new();
+
+    for child in elements(parent) {
+        match child.tag_name().name() {
+            "state" => substates.push(add_state(builder, child)?),
+            "initial" => initial = Some(initial_target(child)?),
+            "transition" | "onentry" | "onexit" if is_element(parent, "state") => {}
+            _ => return Err(unsupported(child)),
+        }
+    }
+
+    let Some(enter_state) = initial.or(substates.first().copied()) else {
+        return Ok(());
+    };
+    if !substates.contains(&enter_state) {
+        return Err(Error::Parse(format!(
+            "Initial state '{enter_state}' of <{}> must be one of its child states",
+            parent.attribute("id").unwrap_or(parent.tag_name().name())
+        )));
+    }
+    builder.add_state(enter_state, StateType::Enter);
+    Ok(())
+}
+
+fn add_state<'a>(builder: &mut UmlFsmBuilder, node: Node<'a, '_>) -> Result<&'a str> {
+    let id = required_attribute(node, "id")?;
+    if id.contains('.') || id.contains("::") {
+        let position = position(node);
+        return Err(Error::Syntax(
+            format!(
+                "Invalid state id '{id}' in line {}: '.' and '::' separate the names of nested states",
+                position.line
+            ),
+            position,
+        ));
+    }
+    let state = builder.add_state(id, StateType::Simple);
+
+    for child in elements(node) {
+        match child.tag_name().name() {
+            "transition" => add_transition(builder, id, child)?,
+            "onentry" => {
+                if let Some(action) = action(child)? {
+                    builder.add_enter_action(id, action);
+                }
+            }
+            "onexit" => {
+                if let Some(action) = action(child)? {
+                    builder.add_exit_action(id, action);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let previous_scope = builder.set_scope(Some(state));
+    add_substates(builder, node)?;
+    builder.set_scope(previous_scope);
+    Ok(id)
+}
+
+fn add_transition(builder: &mut UmlFsmBuilder, source: &str, node: Node<'_, '_>) -> Result<()> {
+    let event = node.attribute("event").map(str::trim);
+    if event.is_some_and(|e| e.contains(char::is_whitespace)) {
+        return Err(unsupported_because(node, "event lists are not supported"));
+    }
+    let target = node.attribute("target").map(str::trim);
+    if target.is_some_and(|t| t.contains(char::is_whitespace)) {
+        return Err(unsupported_because(
+            node,
+            "multiple targets are not supported",
+        ));
+    }
+
+    builder.add_transition(TransitionParameters {
+        source,
+        target,
+        event: event.map(Event::from),
+        action: action(node)?,
+        guard: node.attribute("cond").map(|c| Action::from(c.trim())),
+    });
+    Ok(())
+}
+
+/// The target of the transition within an `<initial>` element
+fn initial_target<'a>(node: Node<'a, '_>) -> Result<&'a str> {
+    elements(node)
+        .find(|child| is_element(*child, "transition"))
+        .ok_or_else(|| unsupported_because(node, "a <transition> child is required"))
+        .and_then(|transition| required_attribute(transition, "target"))
+}
+
+/// The executable content of a transition, `<onentry>` or `<onexit>` element, which may be a
+/// single `<script>` naming the action.
+fn action(node: Node<'_, '_>) -> Result<Option<Action>> {
+    let mut scripts = elements(node).map(|child| match child.tag_name().name() {
+        "script" => child
+            .text()
+            .map(|name| Action::from(name.trim()))
+            .ok_or_else(|| unsupported_because(child, "the action name is missing")),
+        _ => Err(unsupported_because(
+            child,
+            "use <script>ActionName</script> to call an action",
+        )),
+    });
+
+    let action = scripts.next().transpose()?;
+    if let Some(second) = scripts.next() {
+        second?;
+        return Err(unsupported_because(
+            node,
+            "only a single action is supported",
+        ));
+    }
+    Ok(action)
+}
+
+fn elements<'a, 'input>(node: Node<'a, 'input>) -> impl Iterator<Item = Node<'a, 'input>> {
+    node.children().filter(|child| {
+        child.is_element()
+            && child
+                .tag_name()
+                .namespace()
+                .is_none_or(|ns| ns == SCXML_NAMESPACE)
+    })
+}
+
+fn is_element(node: Node<'_, '_>, name: &str) -> bool {
+    node.tag_name().name() == name
+}
+
+fn required_attribute<'a>(node: Node<'a, '_>, attribute: &str) -> Result<&'a str> {
+    node.attribute(attribute).ok_or_else(|| {
+        let position = position(node);
+        Error::Syntax(
+            format!(
+                "Missing attribute '{attribute}' of <{}> in line {}",
+                node.tag_name().name(),
+                position.line
+            ),
+            position,
+        )
+    })
+}
+
+fn unsupported(node: Node<'_, '_>) -> Error {
+    let reason = match node.tag_name().name() {
+        "parallel" => "orthogonal regions are not supported",
+        "history" => "history states are not supported",
+        "final" => "final states are not supported",
+        "datamodel" | "data" => "data models are not supported",
+        "invoke" => "sub state machines are not supported",
+        _ => "the element is not supported here",
+    };
+    unsupported_because(node, reason)
+}
+
+fn unsupported_because(node: Node<'_, '_>, reason: &str) -> Error {
+    let position = position(node);
+    Error::Syntax(
+        format!(
+            "Unsupported SCXML element <{}> in line {}: {reason}",
+            node.tag_name().name(),
+            position.line
+        ),
+        position,
+    )
+}
+
+fn position(node: Node<'_, '_>) -> Position {
+    let pos = node.document().text_pos_at(node.range().start);
+    Position {
+        line: pos.row as usize,
+        column: pos.col as usize,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+    use crate::error::Error;
+    use crate::fsm::StateType;
+
+    fn scxml(content: &str) -> String {
+        format!(
+            r#"<scxml xmlns="http://www.w3.org/2005/07/scxml" version="1.0" name="Test">{content}</scxml>"#
+        )
+    }
+
+    fn assert_unsupported(content: &str, element: &str) {
+        match parse(&scxml(content)) {
+            Err(Error::Syntax(msg, _)) => assert!(
+                msg.starts_with(&format!("Unsupported SCXML element <{element}>")),
+                "{msg}"
+            ),
+            other => panic!("Expected parse error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn first_state_is_initial_by_default() {
+        let fsm = parse(&scxml(r#"<state id="A"/><state id="B"/>"#)).unwrap();
+        assert_eq!(fsm.name(), "Test");
+        assert_eq!(fsm.enter_state().name(), "A");
+    }
+
+    #[test]
+    fn initial_attribute_and_element() {
+        let fsm = parse(&scxml(
+            r#"<state id="A"/>
+               <state id="B">
+                   <initial><transition target="BB"/></initial>
+                   <state id="BA"/>
+                   <state id="BB"/>
+               </state>"#,
+        ))
+        .unwrap();
+        assert_eq!(fsm.enter_state().name(), "A");
+
+        let fsm = parse(
+            &scxml(r#"<state id="A"/><state id="B"/>"#)
+                .replace(r#"name="Test""#, r#"name="Test" initial="B""#),
+        )
+        .unwrap();
+        assert_eq!(fsm.enter_state().name(), "B");
+    }
+
+    #[test]
+    fn nested_states_transitions_and_actions() {
+        let fsm = parse(&scxml(
+            r#"<state id="A" initial="AB">
+                   <onentry><script>EnterA</script></onentry>
+                   <onexit><script>ExitA</script></onexit>
+                   <transition event="Go" cond="CanGo" target="B"><script>Going</script></transition>
+                   <transition event="Stay"/>
+                   <state id="AA"/>
+                   <state id="AB"/>
+               </state>
+               <state id="B"/>"#,
+        ))
+        .unwrap();
+
+        let a = fsm.states().find(|s| s.name() == "A").unwrap();
+        assert_eq!(a.enter_action().map(|a| a.0.as_str()), Some("EnterA"));
+        assert_eq!(a.exit_action().map(|a| a.0.as_str()), Some("ExitA"));
+        assert_eq!(a.enter_state().name(), "AB");
+        let ab = fsm.states().find(|s| s.name() == "AB").unwrap();
+        assert_eq!(ab.state_type(), StateType::Enter);
+        assert_eq!(ab.parent().map(|p| p.name().to_string()), Some("A".into()));
+        assert_eq!(a.transitions().count(), 2);
+    }
+
+    #[test]
+    fn foreign_namespaces_are_ignored() {
+        let fsm = parse(
+            r#"<scxml xmlns="http://www.w3.org/2005/07/scxml" xmlns:qt="http://www.qt.io/2015/02/scxml-ext" name="Test">
+                   <qt:editorinfo initialGeometry="0;0;0;0"/>
+                   <state id="A"><qt:editorinfo geometry="0;0;0;0"/></state>
+               </scxml>"#,
+        )
+        .unwrap();
+        assert_eq!(fsm.states().count(), 1);
+    }
+
+    #[test]
+    fn unsupported_elements_are_named() {
+        assert_unsupported(r#"<parallel id="P"/>"#, "parallel");
+        assert_unsupported(r#"<final id="F"/>"#, "final");
+        assert_unsupported(r#"<state id="A"><history id="H"/></state>"#, "history");
+        assert_unsupported(r#"<datamodel/>"#, "datamodel");
+        assert_unsupported(
+            r#"<state id="A"><onentry><log expr="'hi'"/></onentry></state>"#,
+            "log",
+        );
+        assert_unsupported(
+            r#"<state id="A"><transition event="A B" target="A"/></state>"#,
+            "transition",
+        );
+    }
+
+    #[test]
+    fn error_on_initial_state_outside_of_scope() {
+        let result = parse(
+            &scxml(r#"<state id="A"><state id="AA"/></state>"#)
+                .replace(r#"name="Test""#, r#"name="Test" initial="AA""#),
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn error_on_missing_name() {
+        let result =
+            parse(r#"<scxml xmlns="http://www.w3.org/2005/07/scxml"><state id="A"/></scxml>"#);
+        match result {
+            Err(Error::Parse(msg)) => assert!(msg.contains("requires a 'name' attribute"), "{msg}"),
+            other => panic!("Expected parse error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn error_on_qualified_state_ids() {
+        for id in ["A.B", "A::B"] {
+            let result = parse(&scxml(&format!(r#"<state id="{id}"/>"#)));
+            match result {
+                Err(Error::Syntax(msg, _)) => {
+                    assert!(
+                        msg.starts_with(&format!("Invalid state id '{id}'")),
+                        "{msg}"
+                    )
+                }
+                other => panic!("Expected parse error, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn error_on_invalid_xml() {
+        assert!(parse("<scxml><state></scxml>").is_err());
+        assert!(parse("<notscxml/>").is_err());
+    }
+}
@@ -311,4 +311,12 @@ mod test {
             StateDescription::DeferEvent("SomeEvent".to_owned().into())
         );
     }
+
+    #[test]
+    fn parse_unicode_underscore_and_dash_names() {
+        let desc = TransitionLabel::try_from("go-to_Ä [kann_gehen] / tu-was").unwrap();
+        assert_eq!(desc.event, Some("go-to_Ä".to_owned().into()));
+        assert_eq!(desc.guard, Some("kann_gehen".to_owned().into()));
+        assert_eq!(desc.action, Some("tu-was".to_owned().into()));
+    }
 }
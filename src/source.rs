@@ -0,0 +1,112 @@
+use proc_macro2::Span;
+use syn::LitStr;
+
+use crate::error::{Error, Position};
+
+/// Where the diagram is read from
+pub enum Source {
+    /// Path of the diagram file, see [`crate::file::FilePath::resolve`]
+    File(String),
+    /// Diagram given as string literal within the macro invocation
+    Inline(InlineSource),
+    /// Directory of diagram files, resolved like [`Source::File`]
+    Dir(String),
+}
+
+pub struct InlineSource(LitStr);
+
+impl InlineSource {
+    pub fn new(lit: LitStr) -> Self {
+        Self(lit)
+    }
+
+    pub fn content(&self) -> String {
+        self.0.value()
+    }
+
+    /// The span of the error position within the literal and the error message. Falls back to
+    /// the whole literal if the error has no position, the literal contains escapes or the
+    /// compiler does not support subspans, which are only available on nightly. The message then
+    /// tells the line and column within the literal, counted from its opening quote.
+    pub fn locate(&self, error: &Error) -> (Span, String) {
+        let token = self.0.token();
+        let token_text = token.to_string();
+        let subspan = error
+            .position()
+            .filter(|_| token_text.starts_with('r'))
+            .and_then(|position| {
+                let prefix = token_text.find('"')? + 1;
+                let offset = prefix + offset_of(&self.content(), position)?;
+                token.subspan(offset..offset + 1)
+            });
+        match (subspan, error.position()) {
+            (Some(span), _) => (span, error.to_string()),
+            (None, Some(position)) => (
+                self.0.span(),
+                format!(
+                    "{error} (at line {}, column {} of the inline source)",
+                    position.line, position.column
+                ),
+            ),
+            (None, None) => (self.0.span(), error.to_string()),
+        }
+    }
+}
+
+/// Byte offset of a 1-based line and column (in characters) within the content, if the content
+/// has a character at that position
+fn offset_of(content: &str, position: Position) -> Option<usize> {
+    let line_index = position.line.checked_sub(1)?;
+    let line_start: usize = content
+        .split_inclusive('\n')
+        .take(line_index)
+        .map(str::len)
+        .sum();
+    let line = content.split_inclusive('\n').nth(line_index)?;
+    let (column, _) = line.char_indices().nth(position.column.checked_sub(1)?)?;
+    Some(line_start + column)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{InlineSource, offset_of};
+    use crate::error::{Error, Position};
+
+    #[test]
+    fn offset_of_position() {
+        let content = "@startuml\nÜber --> B\n";
+        let offset = |line, column| offset_of(content, Position { line, column });
+        assert_eq!(offset(1, 1), Some(0));
+        assert_eq!(offset(2, 1), Some(10));
+        assert_eq!(offset(2, 6), Some(16));
+        assert_eq!(offset(2, 11), Some(21));
+    }
+
+    #[test]
+    fn offset_of_missing_position() {
+        let content = "@startuml\nÜber --> B\n";
+        let offset = |line, column| offset_of(content, Position { line, column });
+        assert_eq!(offset(0, 1), None);
+        assert_eq!(offset(1, 0), None);
+        assert_eq!(offset(2, 12), None);
+        assert_eq!(offset(3, 1), None);
+    }
+
+    #[test]
+    fn located_message_tells_the_position_without_subspans() {
+        let lit: syn::LitStr = syn::parse_str("r\"\n@startuml\n  A -> \n\"").unwrap();
+        let source = InlineSource::new(lit);
+        let error = Error::Syntax(
+            "expected state".to_string(),
+            Position { line: 3, column: 8 },
+        );
+        let (_, message) = source.locate(&error);
+        assert_eq!(
+            message,
+            "Parse error: expected state (at line 3, column 8 of the inline source)"
+        );
+
+        let (_, message) = source.locate(&Error::Parse("no states".to_string()));
+        assert_eq!(message, "Parse error: no states");
+    }
+}
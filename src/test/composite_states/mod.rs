@@ -118,6 +118,17 @@ fn build_same_name_substates_fsm() -> Result<UmlFsm> {
         guard: None,
     });
 
+    // Qualified references to substates sharing their name
+    builder.set_scope(None);
+    builder.add_enter_action("ParentA.Inner", Action("EnterInnerA".into()));
+    builder.add_transition(TransitionParameters {
+        source: "ParentB::Other",
+        target: Some("ParentA.Inner"),
+        event: Some(Event("backToA".into())),
+        action: Some(Action("BackToInnerA".into())),
+        guard: None,
+    });
+
     builder.build()
 }
 
@@ -0,0 +1,75 @@
+use crate::{
+    error::Result,
+    fsm::{Action, Event, StateType, TransitionParameters, UmlFsm, UmlFsmBuilder},
+    test::{FsmTestData, utils::get_adjacent_file_path},
+};
+
+fn build_door_fsm() -> Result<UmlFsm> {
+    let mut builder = UmlFsmBuilder::new("Mermaid Door");
+
+    // Root level
+    let closed = builder.add_state("Closed", StateType::Enter);
+    builder.add_state("Opened", StateType::Simple);
+    builder.add_transition(TransitionParameters {
+        source: "Closed",
+        target: Some("Opened"),
+        event: Some(Event("Push".into())),
+        action: None,
+        guard: Some(Action("IsUnlocked".into())),
+    });
+    builder.add_transition(TransitionParameters {
+        source: "Opened",
+        target: Some("Closed"),
+        event: Some(Event("Pull".into())),
+        action: None,
+        guard: None,
+    });
+    builder.add_enter_action("Opened", Action("Ventilate".into()));
+
+    // Closed children
+    builder.set_scope(Some(closed));
+    builder.add_state("Unlocked", StateType::Enter);
+    builder.add_state("CheckCode", StateType::Simple);
+    builder.add_transition(TransitionParameters {
+        source: "Unlocked",
+        target: Some("Locked"),
+        event: Some(Event("Lock".into())),
+        action: Some(Action("Locking".into())),
+        guard: None,
+    });
+    builder.add_transition(TransitionParameters {
+        source: "Locked",
+        target: Some("CheckCode"),
+        event: Some(Event("EnterCode".into())),
+        action: None,
+        guard: None,
+    });
+    builder.add_transition(TransitionParameters {
+        source: "CheckCode",
+        target: Some("Unlocked"),
+        event: None,
+        action: Some(Action("Unlocking".into())),
+        guard: Some(Action("CodeValid".into())),
+    });
+    builder.add_transition(TransitionParameters {
+        source: "CheckCode",
+        target: Some("Locked"),
+        event: None,
+        action: None,
+        guard: Some(Action("CodeInvalid".into())),
+    });
+
+    builder.build()
+}
+
+impl FsmTestData {
+    pub fn mermaid_door() -> Self {
+        let path = get_adjacent_file_path(file!(), "door.mmd");
+        Self {
+            name: "mermaid_door",
+            content: include_str!("./door.mmd"),
+            parsed: build_door_fsm().expect("Failed to create expected FSM"),
+            path,
+        }
+    }
+}
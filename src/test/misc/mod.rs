@@ -1,6 +1,6 @@
 use crate::{
     error::Result,
-    fsm::{Event, StateType, TransitionParameters, UmlFsm, UmlFsmBuilder},
+    fsm::{Action, Event, StateType, TransitionParameters, UmlFsm, UmlFsmBuilder},
     test::{FsmTestData, utils::get_adjacent_file_path},
 };
 
@@ -31,6 +31,27 @@ fn build_internal_names_fsm() -> Result<UmlFsm> {
     builder.build()
 }
 
+fn build_naming_fsm() -> Result<UmlFsm> {
+    let mut builder = UmlFsmBuilder::new("Naming");
+    builder.add_state("Überhitzt", StateType::Enter);
+    builder.add_transition(TransitionParameters {
+        source: "Überhitzt",
+        target: Some("cool_down"),
+        event: Some(Event("go-to-cool".into())),
+        action: Some(Action("type".into())),
+        guard: None,
+    });
+    builder.add_transition(TransitionParameters {
+        source: "cool_down",
+        target: Some("Überhitzt"),
+        event: Some(Event("Match".into())),
+        action: Some(Action("heat_up".into())),
+        guard: Some(Action("loop".into())),
+    });
+    builder.add_enter_action("cool_down", Action::from("self"));
+    builder.build()
+}
+
 impl FsmTestData {
     pub fn misc() -> Self {
         let path = get_adjacent_file_path(file!(), "internal_names.puml");
@@ -41,4 +62,14 @@ impl FsmTestData {
             path,
         }
     }
+
+    pub fn naming() -> Self {
+        let path = get_adjacent_file_path(file!(), "naming.puml");
+        Self {
+            name: "naming",
+            content: include_str!("./naming.puml"),
+            parsed: build_naming_fsm().expect("Failed to create expected FSM"),
+            path,
+        }
+    }
 }
@@ -1,11 +1,13 @@
 use std::path::PathBuf;
 
-use crate::fsm;
+use crate::{fsm, parser::Format};
 mod actions;
 mod composite_states;
 mod deferred_events;
 mod four_seasons;
+mod mermaid;
 mod misc;
+mod scxml;
 mod transitions;
 mod utils;
 
@@ -27,6 +29,10 @@ impl std::fmt::Debug for FsmTestData {
 }
 
 impl FsmTestData {
+    pub fn format(&self) -> Format {
+        Format::from_extension(self.path.extension().and_then(|e| e.to_str()))
+    }
+
     pub fn all() -> impl Iterator<Item = Self> {
         // TODO lazy iter
         vec![
@@ -35,6 +41,7 @@ impl FsmTestData {
             Self::enter_exit(),
             Self::four_seasons(),
             Self::misc(),
+            Self::naming(),
             Self::guards(),
             Self::internal_transitions(),
             Self::same_name_substates(),
@@ -42,6 +49,8 @@ impl FsmTestData {
             Self::transitions(),
             Self::direct_transitions(),
             Self::deferred_events(),
+            Self::mermaid_door(),
+            Self::scxml_traffic_light(),
         ]
         .into_iter()
     }
@@ -0,0 +1,69 @@
+use crate::{
+    error::Result,
+    fsm::{Action, Event, StateType, TransitionParameters, UmlFsm, UmlFsmBuilder},
+    test::{FsmTestData, utils::get_adjacent_file_path},
+};
+
+fn build_traffic_light_fsm() -> Result<UmlFsm> {
+    let mut builder = UmlFsmBuilder::new("TrafficLight");
+
+    // Root level
+    let operating = builder.add_state("Operating", StateType::Enter);
+    builder.add_state("Blinking", StateType::Simple);
+    builder.add_transition(TransitionParameters {
+        source: "Operating",
+        target: Some("Blinking"),
+        event: Some(Event("Fault".into())),
+        action: Some(Action("ReportFault".into())),
+        guard: None,
+    });
+    builder.add_transition(TransitionParameters {
+        source: "Blinking",
+        target: Some("Operating"),
+        event: Some(Event("Repair".into())),
+        action: None,
+        guard: None,
+    });
+
+    // Operating children
+    builder.set_scope(Some(operating));
+    builder.add_state("Red", StateType::Enter);
+    builder.add_state("Green", StateType::Simple);
+    builder.add_enter_action("Red", Action("StopTraffic".into()));
+    builder.add_exit_action("Green", Action("WarnTraffic".into()));
+    builder.add_transition(TransitionParameters {
+        source: "Red",
+        target: Some("Green"),
+        event: Some(Event("Tick".into())),
+        action: None,
+        guard: None,
+    });
+    builder.add_transition(TransitionParameters {
+        source: "Green",
+        target: Some("Red"),
+        event: Some(Event("Tick".into())),
+        action: None,
+        guard: Some(Action("PedestrianWaiting".into())),
+    });
+    builder.add_transition(TransitionParameters {
+        source: "Green",
+        target: None,
+        event: Some(Event("Request".into())),
+        action: Some(Action("RegisterRequest".into())),
+        guard: None,
+    });
+
+    builder.build()
+}
+
+impl FsmTestData {
+    pub fn scxml_traffic_light() -> Self {
+        let path = get_adjacent_file_path(file!(), "traffic_light.scxml");
+        Self {
+            name: "scxml_traffic_light",
+            content: include_str!("./traffic_light.scxml"),
+            parsed: build_traffic_light_fsm().expect("Failed to create expected FSM"),
+            path,
+        }
+    }
+}
@@ -32,8 +32,8 @@ fn actions_are_called_when_transitioning() {
         .returning(|_| ())
         .times(1);
     let mut fsm = test_fsm::start(actions);
-    fsm.go_to_b(());
-    fsm.go_to_a(param_for_action2);
+    let _ = fsm.go_to_b(());
+    let _ = fsm.go_to_a(param_for_action2);
 }
 
 #[test]
@@ -43,5 +43,5 @@ fn no_action_called_when_no_transition() {
     actions.expect_action2().times(0);
     let mut fsm = test_fsm::start(actions);
     // Trigger event that does not cause a transition from the initial state
-    fsm.go_to_a(42);
+    let _ = fsm.go_to_a(42);
 }
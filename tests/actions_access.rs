@@ -0,0 +1,79 @@
+/// Access to the actions owned by the FSM.
+///
+/// Covers:
+/// - Reading and changing the actions through references
+/// - Taking the actions back without running exit actions
+/// - Stopping the FSM, which runs the exit actions from the current state outwards
+use phyto_fsm::generate_fsm;
+generate_fsm!(
+    source = r#"
+    @startuml Counter
+    [*] --> Idle
+    Idle --> Counting : Begin
+    state Counting {
+      Counting : exit / Report
+      [*] --> Active
+      Active : exit / Pause
+      Active : Tick / Count
+    }
+    @enduml
+    "#
+);
+
+use counter::{CounterStateId, ICounterActions, ICounterEventParams};
+
+#[derive(Default)]
+struct Actions {
+    ticks: u32,
+    calls: Vec<&'static str>,
+}
+
+impl ICounterEventParams for Actions {
+    type BeginParams = ();
+    type TickParams = ();
+}
+
+impl ICounterActions for Actions {
+    fn count(&mut self, _: ()) {
+        self.ticks += 1;
+    }
+    fn report(&mut self) {
+        self.calls.push("report");
+    }
+    fn pause(&mut self) {
+        self.calls.push("pause");
+    }
+}
+
+fn counting() -> counter::Counter<Actions> {
+    let mut fsm = counter::start(Actions::default());
+    let _ = fsm.begin(());
+    let _ = fsm.tick(());
+    let _ = fsm.tick(());
+    assert_eq!(fsm.current_state(), CounterStateId::CountingActive);
+    fsm
+}
+
+#[test]
+fn actions_are_accessible_by_reference() {
+    let mut fsm = counting();
+    assert_eq!(fsm.actions().ticks, 2);
+
+    fsm.actions_mut().ticks = 10;
+    let _ = fsm.tick(());
+    assert_eq!(fsm.actions().ticks, 11);
+}
+
+#[test]
+fn into_actions_runs_no_exit_actions() {
+    let actions = counting().into_actions();
+    assert_eq!(actions.ticks, 2);
+    assert!(actions.calls.is_empty());
+}
+
+#[test]
+fn stop_exits_the_active_states() {
+    let actions = counting().stop();
+    assert_eq!(actions.ticks, 2);
+    assert_eq!(actions.calls, vec!["pause", "report"]);
+}
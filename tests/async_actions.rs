@@ -0,0 +1,127 @@
+/// Async actions and guards, run on a single-threaded executor.
+///
+/// Covers:
+/// - Guards, transition actions, enter/exit actions and direct transitions are awaited in order
+/// - Actions which suspend before completing
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use phyto_fsm::generate_fsm;
+generate_fsm!(
+    source = r#"
+    @startuml Fetcher
+    [*] --> Idle
+    Idle --> Busy : Request [IsOnline] / Send
+    state Busy {
+      Busy : entry / Connect
+      Busy : exit / Disconnect
+      [*] --> Waiting
+      Waiting : entry / StartTimer
+    }
+    Waiting --> Done : Response / Store
+    Done --> Idle : / Reset
+    @enduml
+    "#,
+    async_actions = true
+);
+
+use fetcher::{FetcherOutcome, FetcherStateId, IFetcherActions, IFetcherEventParams};
+
+/// Returns pending once, so that the executor has to poll again
+#[derive(Default)]
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+#[derive(Default)]
+struct Actions {
+    calls: Vec<String>,
+}
+
+impl Actions {
+    async fn record(&mut self, call: &str) {
+        YieldNow::default().await;
+        self.calls.push(call.to_string());
+    }
+}
+
+impl IFetcherEventParams for Actions {
+    type RequestParams = bool;
+    type ResponseParams = String;
+}
+
+impl IFetcherActions for Actions {
+    async fn send(&mut self, _: bool) {
+        self.record("send").await;
+    }
+    async fn store(&mut self, response: String) {
+        self.record(&format!("store {response}")).await;
+    }
+    async fn reset(&mut self) {
+        self.record("reset").await;
+    }
+    async fn connect(&mut self) {
+        self.record("connect").await;
+    }
+    async fn start_timer(&mut self) {
+        self.record("start_timer").await;
+    }
+    async fn disconnect(&mut self) {
+        self.record("disconnect").await;
+    }
+    async fn is_online(&self, online: &bool) -> bool {
+        YieldNow::default().await;
+        *online
+    }
+}
+
+#[test]
+fn actions_are_awaited_in_order() {
+    let calls = pollster::block_on(async {
+        let mut fsm = fetcher::start(Actions::default()).await;
+
+        assert_eq!(fsm.request(false).await, FetcherOutcome::GuardRejected);
+        assert_eq!(fsm.current_state(), FetcherStateId::Idle);
+        assert!(fsm.actions().calls.is_empty());
+
+        let _ = fsm.request(true).await;
+        assert_eq!(fsm.current_state(), FetcherStateId::BusyWaiting);
+
+        let outcome = fsm.response("data".to_string()).await;
+        assert_eq!(
+            outcome,
+            FetcherOutcome::Transitioned {
+                from: FetcherStateId::BusyWaiting,
+                to: FetcherStateId::Idle,
+            }
+        );
+        fsm.into_actions().calls
+    });
+
+    assert_eq!(
+        calls,
+        vec![
+            "send",
+            "connect",
+            "start_timer",
+            "store data",
+            "disconnect",
+            "reset",
+        ]
+    );
+}
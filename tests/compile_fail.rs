@@ -0,0 +1,9 @@
+/// Diagnostics of misusing the generated code.
+///
+/// Covers:
+/// - Dropping the outcome of an event, which is `#[must_use]`, also of `async` events
+#[test]
+fn compile_fail() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
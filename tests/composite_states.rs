@@ -30,7 +30,7 @@ fn should_change_between_nested_substates() {
     actions.expect_action_in_aaa().returning(|_| ()).times(1);
 
     let mut fsm = composite_states::start(actions);
-    fsm.to_aab(());
+    let _ = fsm.to_aab(());
 }
 
 #[test]
@@ -40,7 +40,7 @@ fn should_change_between_substates() {
     // the event
     actions.expect_action_in_aa().returning(|_| ()).times(1);
     let mut fsm = composite_states::start(actions);
-    fsm.to_ab(());
+    let _ = fsm.to_ab(());
 }
 
 #[test]
@@ -50,5 +50,32 @@ fn should_change_between_top_level_states() {
     // the event
     actions.expect_action_in_a().returning(|_| ()).times(1);
     let mut fsm = composite_states::start(actions);
-    fsm.to_b(());
+    let _ = fsm.to_b(());
+}
+
+#[test]
+fn should_report_active_states() {
+    use composite_states::CompositeStatesStateId as StateId;
+
+    let mut actions = MockCompositeStatesActions::new();
+    actions.expect_action_in_aa().returning(|_| ()).times(1);
+    let mut fsm = composite_states::start(actions);
+
+    assert_eq!(fsm.current_state(), StateId::StateAStateAaStateAaa);
+    assert_eq!(
+        fsm.active_states().collect::<Vec<_>>(),
+        vec![
+            StateId::StateAStateAaStateAaa,
+            StateId::StateAStateAa,
+            StateId::StateA
+        ]
+    );
+    assert!(fsm.is_in(StateId::StateA));
+    assert!(fsm.is_in(StateId::StateAStateAa));
+    assert!(!fsm.is_in(StateId::StateB));
+
+    let _ = fsm.to_ab(());
+    assert_eq!(fsm.current_state(), StateId::StateAStateAb);
+    assert!(fsm.is_in(StateId::StateA));
+    assert!(!fsm.is_in(StateId::StateAStateAa));
 }
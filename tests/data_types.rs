@@ -23,8 +23,8 @@ impl ITestFsmActions for ActionsWithClonableData {
 fn actions_with_clonable_data() {
     let actions = ActionsWithClonableData;
     let mut fsm = test_fsm::start(actions);
-    fsm.go_to_b("Hello FSM".to_string());
-    fsm.go_to_a(vec![10, 20, 30]);
+    let _ = fsm.go_to_b("Hello FSM".to_string());
+    let _ = fsm.go_to_a(vec![10, 20, 30]);
 }
 
 struct ActionsWithPointers<'a> {
@@ -55,8 +55,33 @@ fn actions_with_pointers() {
         phantom: std::marker::PhantomData,
     };
     let mut fsm = test_fsm::start(actions);
-    fsm.go_to_b("Pointer Event");
+    let _ = fsm.go_to_b("Pointer Event");
     let number: i32 = 100;
     let number_ptr: *const i32 = &number as *const i32;
-    fsm.go_to_a(number_ptr);
+    let _ = fsm.go_to_a(number_ptr);
+}
+
+#[test]
+fn trigger_events_from_a_table() {
+    use test_fsm::{TestFsmEvent, TestFsmEventKind, TestFsmStateId};
+
+    let events: Vec<TestFsmEvent<ActionsWithClonableData>> = vec![
+        TestFsmEvent::GoToB("Hello FSM".to_string()),
+        TestFsmEvent::GoToA(vec![1, 2]),
+        TestFsmEvent::GoToB("Again".to_string()),
+    ];
+    let mut fsm = test_fsm::start(ActionsWithClonableData);
+    for event in events {
+        let _ = fsm.trigger(event);
+    }
+    assert_eq!(fsm.current_state(), TestFsmStateId::StateB);
+
+    let event: TestFsmEvent<ActionsWithClonableData> = TestFsmEvent::GoToA(vec![10, 20]);
+    assert_eq!(format!("{event:?}"), "GoToA([10, 20])");
+    assert_eq!(event.to_string(), "GoToA");
+    assert_eq!(event.kind(), TestFsmEventKind::GoToA);
+    assert_eq!(
+        TestFsmEventKind::ALL,
+        [TestFsmEventKind::GoToB, TestFsmEventKind::GoToA]
+    );
 }
@@ -0,0 +1,62 @@
+/// The inline queue of deferred events with a fixed capacity.
+///
+/// Covers:
+/// - Deferred events are processed in order after the transition
+/// - Events deferred while the queue is full are discarded and reported as unhandled
+use phyto_fsm::generate_fsm;
+generate_fsm!(
+    source = r#"
+    @startuml Printer
+    [*] --> WarmingUp
+    WarmingUp : Print /defer
+    WarmingUp --> Ready : Warm
+    Ready --> Ready : Print / PrintPage
+    @enduml
+    "#,
+    deferred_capacity = 2
+);
+
+use mockall::{Sequence, mock, predicate::eq};
+use printer::{
+    IPrinterActions, IPrinterEventParams, PrinterEventKind, PrinterOutcome, PrinterStateId,
+};
+
+mock! {
+    PrinterActions {}
+    impl IPrinterActions for PrinterActions {
+        fn print_page(&mut self, page: u32);
+        fn on_unhandled(&mut self, state: PrinterStateId, event: PrinterEventKind);
+    }
+}
+
+impl IPrinterEventParams for MockPrinterActions {
+    type PrintParams = u32;
+    type WarmParams = ();
+}
+
+#[test]
+fn overflowing_events_are_discarded() {
+    let mut actions = MockPrinterActions::new();
+    let mut seq = Sequence::new();
+    actions
+        .expect_on_unhandled()
+        .with(eq(PrinterStateId::WarmingUp), eq(PrinterEventKind::Print))
+        .times(1)
+        .in_sequence(&mut seq)
+        .returning(|_, _| ());
+    for page in [1, 2] {
+        actions
+            .expect_print_page()
+            .with(eq(page))
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_| ());
+    }
+
+    let mut fsm = printer::start(actions);
+    assert_eq!(fsm.print(1), PrinterOutcome::Deferred);
+    assert_eq!(fsm.print(2), PrinterOutcome::Deferred);
+    assert_eq!(fsm.print(3), PrinterOutcome::Ignored);
+    let _ = fsm.warm(());
+    assert_eq!(fsm.current_state(), PrinterStateId::Ready);
+}
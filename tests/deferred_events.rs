@@ -46,11 +46,11 @@ fn deferred_event_fires_after_leaving_deferring_state() {
     let mut fsm = deferred_events::start(actions);
 
     // In StateA: GoToA is deferred
-    fsm.go_to_a(());
+    let _ = fsm.go_to_a(());
 
     // GoToB transitions StateA -> StateB
     // Deferred GoToA fires in StateB: StateB -> StateA (enter_a called again)
-    fsm.go_to_b(());
+    let _ = fsm.go_to_b(());
 }
 
 #[test]
@@ -65,13 +65,13 @@ fn event_is_redeferred() {
     let mut fsm = deferred_events::start(actions);
 
     // GoToA is deferred in StateA
-    fsm.go_to_a(());
+    let _ = fsm.go_to_a(());
 
     // GoToC: StateA -> StateC. Deferred GoToA is re-evaluated: also deferred in StateC (re-deferred)
-    fsm.go_to_c(());
+    let _ = fsm.go_to_c(());
 
     // GoToBFromC: StateC -> StateB. Deferred GoToA fires: StateB -> StateA
-    fsm.go_to_b_from_c(());
+    let _ = fsm.go_to_b_from_c(());
 }
 
 #[test]
@@ -86,11 +86,11 @@ fn deferred_event_fires_after_direct_transition() {
     let mut fsm = deferred_events::start(actions);
 
     // GoToA is deferred in StateA
-    fsm.go_to_a(());
+    let _ = fsm.go_to_a(());
 
     // GoToD: StateA -> StateD, direct transition StateD -> StateB
     // Deferred GoToA fires in StateB: StateB -> StateA
-    fsm.go_to_d(());
+    let _ = fsm.go_to_d(());
 }
 
 #[test]
@@ -105,10 +105,10 @@ fn substate_inherits_defer() {
 
     let mut fsm = deferred_events::start(actions);
 
-    fsm.go_to_f(());
+    let _ = fsm.go_to_f(());
     // GoToA is deferred in StateF
-    fsm.go_to_a(());
+    let _ = fsm.go_to_a(());
 
     // Deferred GoTo fires
-    fsm.go_to_b_from_f(());
+    let _ = fsm.go_to_b_from_f(());
 }
@@ -0,0 +1,93 @@
+/// Generating all diagrams of a directory.
+///
+/// Covers:
+/// - A module for every `.puml` file of the directory with `generate_fsms!`
+/// - Selecting the files with the `glob` option
+/// - The files of the `format` by default
+use mockall::mock;
+use phyto_fsm::generate_fsms;
+generate_fsms!(dir = "test/dir");
+
+use door::{IDoorActions, IDoorEventParams};
+use light::{ILightActions, ILightEventParams};
+
+mock! {
+    DoorActions {}
+    impl IDoorActions for DoorActions {
+        fn door_opened(&mut self, event: <MockDoorActions as IDoorEventParams>::PushParams);
+        fn door_closed(&mut self, event: <MockDoorActions as IDoorEventParams>::PullParams);
+    }
+}
+
+impl IDoorEventParams for MockDoorActions {
+    type PushParams = ();
+    type PullParams = ();
+}
+
+mock! {
+    LightActions {}
+    impl ILightActions for LightActions {
+        fn switched_on(&mut self, event: <MockLightActions as ILightEventParams>::ToggleParams);
+        fn switched_off(&mut self, event: <MockLightActions as ILightEventParams>::ToggleParams);
+    }
+}
+
+impl ILightEventParams for MockLightActions {
+    type ToggleParams = ();
+}
+
+#[test]
+fn all_files_are_generated() {
+    let mut door_actions = MockDoorActions::new();
+    door_actions.expect_door_opened().returning(|_| ()).once();
+    let mut light_actions = MockLightActions::new();
+    light_actions.expect_switched_on().returning(|_| ()).once();
+
+    let mut door = door::start(door_actions);
+    let mut light = light::start(light_actions);
+    let _ = door.push(());
+    let _ = light.toggle(());
+}
+
+mod glob {
+    use mockall::mock;
+    phyto_fsm::generate_fsms!(dir = "test/dir", glob = "nested/*.puml");
+
+    use bell::{IBellActions, IBellEventParams};
+
+    mock! {
+        BellActions {}
+        impl IBellActions for BellActions {
+            fn start_ringing(&mut self, event: <MockBellActions as IBellEventParams>::PressParams);
+            fn stop_ringing(&mut self, event: <MockBellActions as IBellEventParams>::ReleaseParams);
+        }
+    }
+
+    impl IBellEventParams for MockBellActions {
+        type PressParams = ();
+        type ReleaseParams = ();
+    }
+
+    #[test]
+    fn matching_files_are_generated() {
+        let mut actions = MockBellActions::new();
+        actions.expect_start_ringing().returning(|_| ()).once();
+        actions.expect_stop_ringing().returning(|_| ()).once();
+
+        let mut fsm = bell::start(actions);
+        let _ = fsm.press(());
+        let _ = fsm.release(());
+    }
+}
+
+mod format {
+    phyto_fsm::generate_fsms!(dir = "test/scxml", format = "scxml");
+
+    #[test]
+    fn files_of_the_format_are_generated() {
+        assert_eq!(
+            traffic_light::TrafficLightStateId::OperatingRed.to_string(),
+            "Operating::Red"
+        );
+    }
+}
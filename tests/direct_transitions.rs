@@ -74,5 +74,5 @@ fn direct_transition_after_event() {
     let mut fsm = direct_transitions::start(actions);
 
     // Event-based transition back to StateA, which triggers direct to StateB again
-    fsm.goto_a(());
+    let _ = fsm.goto_a(());
 }
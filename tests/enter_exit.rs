@@ -121,7 +121,7 @@ fn exit_action_called_when_leaving_state() {
     t.expect_exit_a();
 
     let mut fsm = enter_exit_actions::start(t.actions);
-    fsm.go_to_b(());
+    let _ = fsm.go_to_b(());
 }
 
 #[test]
@@ -134,8 +134,8 @@ fn enter_action_called_when_entering_state() {
     t.expect_enter_a();
 
     let mut fsm = enter_exit_actions::start(t.actions);
-    fsm.go_to_b(());
-    fsm.go_to_a_from_b(());
+    let _ = fsm.go_to_b(());
+    let _ = fsm.go_to_a_from_b(());
 }
 
 #[test]
@@ -145,7 +145,7 @@ fn parent_enter_before_substate_enter() {
     t.expect_a_to_c1();
 
     let mut fsm = enter_exit_actions::start(t.actions);
-    fsm.go_to_c1_from_a(());
+    let _ = fsm.go_to_c1_from_a(());
 }
 
 #[test]
@@ -160,8 +160,8 @@ fn substate_exit_before_parent_exit() {
     t.expect_enter_a();
 
     let mut fsm = enter_exit_actions::start(t.actions);
-    fsm.go_to_c1_from_a(());
-    fsm.go_to_a_from_c(());
+    let _ = fsm.go_to_c1_from_a(());
+    let _ = fsm.go_to_a_from_c(());
 }
 
 #[test]
@@ -171,7 +171,7 @@ fn substate_entry_defaults_to_parent_enter() {
     t.expect_a_to_c2();
 
     let mut fsm = enter_exit_actions::start(t.actions);
-    fsm.go_to_c2_from_a(());
+    let _ = fsm.go_to_c2_from_a(());
 }
 
 #[test]
@@ -184,8 +184,8 @@ fn substate_exit_defaults_to_parent_exit() {
     t.expect_enter_a();
 
     let mut fsm = enter_exit_actions::start(t.actions);
-    fsm.go_to_c2_from_a(());
-    fsm.go_to_a_from_c(());
+    let _ = fsm.go_to_c2_from_a(());
+    let _ = fsm.go_to_a_from_c(());
 }
 
 #[test]
@@ -199,8 +199,8 @@ fn internal_substate_transition_only_calls_substate_actions() {
     t.actions.expect_exit_c().never();
 
     let mut fsm = enter_exit_actions::start(t.actions);
-    fsm.go_to_c1_from_a(());
-    fsm.go_to_c2(());
+    let _ = fsm.go_to_c1_from_a(());
+    let _ = fsm.go_to_c2(());
 }
 
 #[test]
@@ -211,7 +211,7 @@ fn self_transition_calls_exit_and_enter() {
     t.expect_enter_a();
 
     let mut fsm = enter_exit_actions::start(t.actions);
-    fsm.go_to_a_from_a(());
+    let _ = fsm.go_to_a_from_a(());
 }
 
 // TODO internal transitions
@@ -0,0 +1,186 @@
+/// Actions returning a `Result`, with failures routed by `error` transitions.
+///
+/// Covers:
+/// - A failed effect keeps the current state
+/// - Error transitions inherited from the parent state
+/// - Failed enter actions, also when starting the FSM
+/// - A failed exit action when stopping the FSM
+/// - Direct transitions of the error state
+use phyto_fsm::generate_fsm;
+generate_fsm!(
+    source = r#"
+    @startuml Pump
+    [*] --> Idle
+    Idle : entry / Wake
+    Idle --> Running : Start / Prime
+    state Running {
+      [*] --> Pumping
+      Pumping : entry / StartMotor
+      Pumping : exit / StopMotor
+      Pumping --> Pumping : Pulse / Pump
+    }
+    Running --> Idle : Halt
+    Running --> Fault : error
+    Fault --> Idle : Reset
+    @enduml
+    "#,
+    fallible_actions = true
+);
+
+// An error state which is left right away
+generate_fsm!(
+    source = r#"
+    @startuml Fan
+    [*] --> Idle
+    Idle --> Spinning : Spin / Accelerate
+    Spinning --> Idle : Halt
+    Idle --> Fault : error
+    Fault : entry / Alarm
+    Fault --> Idle
+    @enduml
+    "#,
+    fallible_actions = true
+);
+
+use fan::{FanStateId, IFanActions, IFanEventParams};
+use pump::{IPumpActions, IPumpEventParams, PumpOutcome, PumpStateId};
+
+#[derive(Debug, PartialEq)]
+struct PumpError(&'static str);
+
+/// Fails the action with the given name
+#[derive(Default)]
+struct Actions {
+    failing: Option<&'static str>,
+}
+
+impl Actions {
+    fn run(&mut self, action: &'static str) -> Result<(), PumpError> {
+        if self.failing == Some(action) {
+            Err(PumpError(action))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl IPumpEventParams for Actions {
+    type StartParams = ();
+    type PulseParams = ();
+    type HaltParams = ();
+    type ResetParams = ();
+}
+
+impl IPumpActions for Actions {
+    type Error = PumpError;
+
+    fn prime(&mut self, _: ()) -> Result<(), PumpError> {
+        self.run("prime")
+    }
+    fn pump(&mut self, _: ()) -> Result<(), PumpError> {
+        self.run("pump")
+    }
+    fn wake(&mut self) -> Result<(), PumpError> {
+        self.run("wake")
+    }
+    fn start_motor(&mut self) -> Result<(), PumpError> {
+        self.run("start_motor")
+    }
+    fn stop_motor(&mut self) -> Result<(), PumpError> {
+        self.run("stop_motor")
+    }
+}
+
+#[test]
+fn failed_effect_keeps_the_state() {
+    let mut fsm = pump::start(Actions::default()).unwrap();
+
+    fsm.actions_mut().failing = Some("prime");
+    assert_eq!(fsm.start(()), Err(PumpError("prime")));
+    assert_eq!(fsm.current_state(), PumpStateId::Idle);
+
+    fsm.actions_mut().failing = None;
+    assert_eq!(
+        fsm.start(()),
+        Ok(PumpOutcome::Transitioned {
+            from: PumpStateId::Idle,
+            to: PumpStateId::RunningPumping,
+        })
+    );
+}
+
+#[test]
+fn failure_is_routed_to_the_error_state() {
+    let mut fsm = pump::start(Actions::default()).unwrap();
+    let _ = fsm.start(()).unwrap();
+
+    fsm.actions_mut().failing = Some("pump");
+    assert_eq!(fsm.pulse(()), Err(PumpError("pump")));
+    assert_eq!(fsm.current_state(), PumpStateId::Fault);
+
+    fsm.actions_mut().failing = None;
+    let _ = fsm.reset(()).unwrap();
+    assert_eq!(fsm.current_state(), PumpStateId::Idle);
+}
+
+#[test]
+fn failed_enter_action_is_routed_to_the_error_state() {
+    let mut fsm = pump::start(Actions::default()).unwrap();
+
+    fsm.actions_mut().failing = Some("start_motor");
+    assert_eq!(fsm.start(()), Err(PumpError("start_motor")));
+    assert_eq!(fsm.current_state(), PumpStateId::Fault);
+}
+
+#[test]
+fn failed_start_returns_the_error() {
+    let actions = Actions {
+        failing: Some("wake"),
+    };
+    assert!(matches!(pump::start(actions), Err(PumpError("wake"))));
+}
+
+#[test]
+fn failed_stop_gives_the_actions_back() {
+    let mut fsm = pump::start(Actions::default()).unwrap();
+    let _ = fsm.start(()).unwrap();
+
+    fsm.actions_mut().failing = Some("stop_motor");
+    let Err((actions, error)) = fsm.stop() else {
+        panic!("stopping should fail");
+    };
+    assert_eq!(error, PumpError("stop_motor"));
+    assert_eq!(actions.failing, Some("stop_motor"));
+}
+
+#[derive(Default)]
+struct FanActions {
+    calls: Vec<&'static str>,
+}
+
+impl IFanEventParams for FanActions {
+    type SpinParams = ();
+    type HaltParams = ();
+}
+
+impl IFanActions for FanActions {
+    type Error = PumpError;
+
+    fn accelerate(&mut self, _: ()) -> Result<(), PumpError> {
+        self.calls.push("accelerate");
+        Err(PumpError("accelerate"))
+    }
+    fn alarm(&mut self) -> Result<(), PumpError> {
+        self.calls.push("alarm");
+        Ok(())
+    }
+}
+
+#[test]
+fn error_state_takes_its_direct_transitions() {
+    let mut fsm = fan::start(FanActions::default()).unwrap();
+
+    assert_eq!(fsm.spin(()), Err(PumpError("accelerate")));
+    assert_eq!(fsm.current_state(), FanStateId::Idle);
+    assert_eq!(fsm.actions().calls, vec!["accelerate", "alarm"]);
+}
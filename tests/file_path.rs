@@ -0,0 +1,55 @@
+/// Resolution of the `file_path` option.
+///
+/// Covers:
+/// - Expanding environment variables
+/// - Paths relative to the workspace root
+mod env_var {
+    phyto_fsm::generate_fsm!(
+        file_path = "${CARGO_MANIFEST_DIR}/src/test/misc/multiple_diagrams.puml",
+        diagram = "Light"
+    );
+
+    pub struct Actions;
+
+    impl light::ILightActions for Actions {
+        fn switched_on(&mut self, _: ()) {}
+        fn switched_off(&mut self, _: ()) {}
+    }
+
+    impl light::ILightEventParams for Actions {
+        type ToggleParams = ();
+    }
+
+    #[test]
+    fn file_path_with_env_var() {
+        let mut fsm = light::start(Actions);
+        let _ = fsm.toggle(());
+        let _ = fsm.toggle(());
+    }
+}
+
+mod workspace {
+    phyto_fsm::generate_fsm!(
+        file_path = "workspace:src/test/misc/multiple_diagrams.puml",
+        diagram = "Door"
+    );
+
+    pub struct Actions;
+
+    impl door::IDoorActions for Actions {
+        fn door_opened(&mut self, _: ()) {}
+        fn door_closed(&mut self, _: ()) {}
+    }
+
+    impl door::IDoorEventParams for Actions {
+        type PushParams = ();
+        type PullParams = ();
+    }
+
+    #[test]
+    fn file_path_relative_to_workspace() {
+        let mut fsm = door::start(Actions);
+        let _ = fsm.push(());
+        let _ = fsm.pull(());
+    }
+}
@@ -1,7 +1,5 @@
 use std::cell::RefCell;
 use std::collections::VecDeque;
-use std::fmt;
-use std::rc::Rc;
 
 use proptest::prelude::*;
 
@@ -11,73 +9,36 @@ proptest! {
         let ref_guards = build_guard_queue(&events);
         let gen_guards = ref_guards.clone();
 
-        let ref_recorder = ReferenceRecorder::new(ref_guards);
-        let gen_recorder = GeneratedRecorder::new(gen_guards);
-
-        let ref_log = ref_recorder.shared_log();
-        let gen_log = gen_recorder.shared_log();
-
-        let mut ref_fsm = super::reference::start(ref_recorder);
-        let mut gen_fsm = super::plant_fsm::start(gen_recorder);
+        let mut ref_fsm = super::reference::start(ReferenceRecorder::new(ref_guards));
+        let mut gen_fsm = super::plant_fsm::start(GeneratedRecorder::new(gen_guards));
 
         replay_on_reference(&mut ref_fsm, &events);
         replay_on_generated(&mut gen_fsm, &events);
 
-        prop_assert_eq!(ref_log, gen_log);
-    }
-}
-
-#[derive(Clone)]
-struct SharedLog(Rc<RefCell<Vec<String>>>);
-
-impl SharedLog {
-    fn new() -> Self {
-        Self(Rc::new(RefCell::new(Vec::new())))
-    }
-
-    fn record(&self, action: &str) {
-        self.0.borrow_mut().push(action.to_string());
-    }
-}
-
-impl PartialEq for SharedLog {
-    fn eq(&self, other: &Self) -> bool {
-        *self.0.borrow() == *other.0.borrow()
-    }
-}
-
-impl Eq for SharedLog {}
-
-impl fmt::Debug for SharedLog {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.0.borrow().fmt(f)
+        prop_assert_eq!(ref_fsm.into_actions().0.log, gen_fsm.into_actions().0.log);
     }
 }
 
 struct RecorderInner {
-    log: SharedLog,
+    log: Vec<String>,
     guards: RefCell<VecDeque<bool>>,
 }
 
 impl RecorderInner {
     fn new(guards: VecDeque<bool>) -> Self {
         Self {
-            log: SharedLog::new(),
+            log: Vec::new(),
             guards: RefCell::new(guards),
         }
     }
 
-    fn record(&self, action: &str) {
-        self.log.record(action);
+    fn record(&mut self, action: &str) {
+        self.log.push(action.to_string());
     }
 
     fn next_guard(&self) -> bool {
         self.guards.borrow_mut().pop_front().unwrap_or(false)
     }
-
-    fn shared_log(&self) -> SharedLog {
-        self.log.clone()
-    }
 }
 
 struct ReferenceRecorder(RecorderInner);
@@ -87,20 +48,12 @@ impl ReferenceRecorder {
     fn new(guards: VecDeque<bool>) -> Self {
         Self(RecorderInner::new(guards))
     }
-
-    fn shared_log(&self) -> SharedLog {
-        self.0.shared_log()
-    }
 }
 
 impl GeneratedRecorder {
     fn new(guards: VecDeque<bool>) -> Self {
         Self(RecorderInner::new(guards))
     }
-
-    fn shared_log(&self) -> SharedLog {
-        self.0.shared_log()
-    }
 }
 
 macro_rules! impl_recorder {
@@ -191,7 +144,7 @@ fn replay_on_reference(
             FuzzEvent::TemperatureRises => fsm.temperature_rises(()),
             FuzzEvent::TemperatureDrops => fsm.temperature_drops(()),
             FuzzEvent::TimeAdvances(_) => fsm.time_advances(()),
-        }
+        };
     }
 }
 
@@ -200,10 +153,10 @@ fn replay_on_generated(
     events: &[FuzzEvent],
 ) {
     for event in events {
-        match event {
+        let _ = match event {
             FuzzEvent::TemperatureRises => fsm.temperature_rises(()),
             FuzzEvent::TemperatureDrops => fsm.temperature_drops(()),
             FuzzEvent::TimeAdvances(_) => fsm.time_advances(()),
-        }
+        };
     }
 }
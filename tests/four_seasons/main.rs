@@ -80,10 +80,10 @@ fn test_transitions() {
     actions.expect_start_blizzard().never();
 
     let mut fsm = plant_fsm::start(actions);
-    fsm.temperature_rises(());
-    fsm.time_advances(time);
-    fsm.time_advances(time);
-    fsm.temperature_drops(());
-    fsm.time_advances(time);
-    fsm.time_advances(time);
+    let _ = fsm.temperature_rises(());
+    let _ = fsm.time_advances(time);
+    let _ = fsm.time_advances(time);
+    let _ = fsm.temperature_drops(());
+    let _ = fsm.time_advances(time);
+    let _ = fsm.time_advances(time);
 }
@@ -516,6 +516,10 @@ where
     pub fn time_advances(&mut self, params: <A as IPlantFsmEventParams>::TimeAdvancesParams) {
         self.0.run_event_loop(PlantFsmEvent::TimeAdvances(params));
     }
+
+    pub fn into_actions(self) -> A {
+        self.0.actions
+    }
 }
 
 #[cfg(test)]
@@ -40,7 +40,7 @@ fn no_transition_when_all_guards_deny() {
     actions.expect_action_to_c().never();
 
     let mut fsm = guards::start(actions);
-    fsm.change_state(());
+    let _ = fsm.change_state(());
 }
 
 #[test]
@@ -51,7 +51,7 @@ fn guard_allows_specific_transition() {
     actions.expect_action_to_b().returning(|_| ()).once();
 
     let mut fsm = guards::start(actions);
-    fsm.change_state(());
+    let _ = fsm.change_state(());
 }
 
 #[test]
@@ -63,7 +63,7 @@ fn first_matching_guard_wins() {
     actions.expect_action_to_b().never();
 
     let mut fsm = guards::start(actions);
-    fsm.change_state(());
+    let _ = fsm.change_state(());
 }
 
 #[test]
@@ -79,6 +79,6 @@ fn guard_on_composite_substate_transition() {
     actions.expect_action_to_ca().returning(|_| ()).once();
 
     let mut fsm = guards::start(actions);
-    fsm.change_state(()); // StateA -> StateC
-    fsm.change_state(()); // StateC -> StateCa
+    let _ = fsm.change_state(()); // StateA -> StateC
+    let _ = fsm.change_state(()); // StateC -> StateCa
 }
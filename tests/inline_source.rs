@@ -0,0 +1,78 @@
+/// Test FSMs defined inline within the macro invocation.
+///
+/// Covers:
+/// - A bare raw string literal as source
+/// - The `source` option, combined with other options
+use mockall::mock;
+use phyto_fsm::generate_fsm;
+
+generate_fsm!(
+    r#"
+    @startuml Switch
+    [*] --> Off
+    Off --> On : Toggle / TurnOn
+    On --> Off : Toggle / TurnOff
+    @enduml
+    "#
+);
+
+use switch::{ISwitchActions, ISwitchEventParams};
+
+mock! {
+    SwitchActions {}
+    impl ISwitchActions for SwitchActions {
+        fn turn_on(&mut self, event: <MockSwitchActions as ISwitchEventParams>::ToggleParams);
+        fn turn_off(&mut self, event: <MockSwitchActions as ISwitchEventParams>::ToggleParams);
+    }
+}
+
+impl ISwitchEventParams for MockSwitchActions {
+    type ToggleParams = ();
+}
+
+#[test]
+fn bare_raw_string_is_source() {
+    let mut actions = MockSwitchActions::new();
+    actions.expect_turn_on().returning(|_| ()).once();
+    actions.expect_turn_off().returning(|_| ()).once();
+
+    let mut fsm = switch::start(actions);
+    let _ = fsm.toggle(());
+    let _ = fsm.toggle(());
+}
+
+mod with_options {
+    use mockall::mock;
+
+    phyto_fsm::generate_fsm!(
+        source = "---\ntitle: Worker\n---\nstateDiagram-v2\n[*] --> Idle\nIdle --> Busy : Start / Work\n",
+        format = "mermaid",
+        log_level = "debug"
+    );
+
+    use worker::{IWorkerActions, IWorkerEventParams};
+
+    mock! {
+        WorkerActions {}
+        impl IWorkerActions for WorkerActions {
+            fn work(&mut self, event: <MockWorkerActions as IWorkerEventParams>::StartParams);
+        }
+    }
+
+    impl IWorkerEventParams for MockWorkerActions {
+        type StartParams = u8;
+    }
+
+    #[test]
+    fn source_option_with_format() {
+        let mut actions = MockWorkerActions::new();
+        actions
+            .expect_work()
+            .withf(|job| *job == 7)
+            .returning(|_| ())
+            .once();
+
+        let mut fsm = worker::start(actions);
+        let _ = fsm.start(7);
+    }
+}
@@ -99,7 +99,7 @@ fn internal_transition_calls_action_but_not_exit_enter() {
     f.actions.expect_exit_state_a().never();
 
     let mut fsm = f.start();
-    fsm.internal_event(());
+    let _ = fsm.internal_event(());
 }
 
 #[test]
@@ -110,7 +110,7 @@ fn self_transition_calls_action_and_exit_enter() {
     f.expect_enter_state_a();
 
     let mut fsm = f.start();
-    fsm.self_transition_event(());
+    let _ = fsm.self_transition_event(());
 }
 
 // --- StateBa (substate inside composite StateB) ---
@@ -123,8 +123,8 @@ fn composite_internal_transition_does_not_trigger_exit_enter() {
     f.actions.expect_exit_state_b().never();
 
     let mut fsm = f.start();
-    fsm.go_to_b(());
-    fsm.internal_event(());
+    let _ = fsm.go_to_b(());
+    let _ = fsm.internal_event(());
 }
 
 #[test]
@@ -135,6 +135,6 @@ fn composite_self_transition_does_not_trigger_parent_exit_enter() {
     f.actions.expect_exit_state_b().never();
 
     let mut fsm = f.start();
-    fsm.go_to_b(());
-    fsm.self_transition_event(());
+    let _ = fsm.go_to_b(());
+    let _ = fsm.self_transition_event(());
 }
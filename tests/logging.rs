@@ -0,0 +1,87 @@
+/// Log records of every step of the FSM, with a custom target and format.
+///
+/// Covers:
+/// - The initial transition, event based and direct transitions
+/// - Guard rejections and ignored events
+/// - Deferrals and deferred events processed again
+use std::sync::Mutex;
+
+use phyto_fsm::generate_fsm;
+generate_fsm!(
+    source = r#"
+    @startuml Pump
+    [*] --> Idle
+    Idle : Prime /defer
+    Idle --> Priming : Start [HasWater]
+    Priming --> Running
+    Running : Prime / Flush
+    Running --> Idle : Halt
+    @enduml
+    "#,
+    log_level = "info",
+    log_target = "pump",
+    log_format = "<{fsm}> {message}"
+);
+
+use pump::{IPumpActions, IPumpEventParams};
+
+struct Actions;
+
+impl IPumpEventParams for Actions {
+    type PrimeParams = ();
+    type StartParams = bool;
+    type HaltParams = ();
+}
+
+impl IPumpActions for Actions {
+    fn flush(&mut self, _: ()) {}
+    fn has_water(&self, water: &bool) -> bool {
+        *water
+    }
+}
+
+static RECORDS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+struct Recorder;
+
+impl log::Log for Recorder {
+    fn enabled(&self, _: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        RECORDS.lock().unwrap().push(format!(
+            "{} {}: {}",
+            record.level(),
+            record.target(),
+            record.args()
+        ));
+    }
+
+    fn flush(&self) {}
+}
+
+#[test]
+fn every_step_is_logged() {
+    log::set_logger(&Recorder).unwrap();
+    log::set_max_level(log::LevelFilter::Trace);
+
+    let mut fsm = pump::start(Actions);
+    let _ = fsm.start(false);
+    let _ = fsm.halt(());
+    let _ = fsm.prime(());
+    let _ = fsm.start(true);
+
+    assert_eq!(
+        *RECORDS.lock().unwrap(),
+        vec![
+            "INFO pump: <Pump> [*] --> Idle, entering Idle",
+            "INFO pump: <Pump> Idle rejects Start by its guards",
+            "INFO pump: <Pump> Idle ignores Halt",
+            "INFO pump: <Pump> Idle defers Prime",
+            "INFO pump: <Pump> Idle -[Start]-> Priming, entering Priming",
+            "INFO pump: <Pump> Priming --> Running, entering Running",
+            "INFO pump: <Pump> Running processes deferred Prime",
+        ]
+    );
+}
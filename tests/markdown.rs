@@ -0,0 +1,75 @@
+/// Diagrams in the fenced code blocks of a Markdown file.
+///
+/// Covers:
+/// - Generating all PlantUML fences with `generate_fsms!`
+/// - Selecting a single fence with the `fence` option, including Mermaid fences
+use mockall::mock;
+use phyto_fsm::{generate_fsm, generate_fsms};
+generate_fsms!(file_path = "test/misc/design.md", format = "plantuml");
+generate_fsm!(file_path = "test/misc/design.md", fence = "Bell");
+
+use bell::{IBellActions, IBellEventParams};
+use door::{IDoorActions, IDoorEventParams};
+use light::{ILightActions, ILightEventParams};
+
+mock! {
+    DoorActions {}
+    impl IDoorActions for DoorActions {
+        fn door_opened(&mut self, event: <MockDoorActions as IDoorEventParams>::PushParams);
+        fn door_closed(&mut self, event: <MockDoorActions as IDoorEventParams>::PullParams);
+    }
+}
+
+impl IDoorEventParams for MockDoorActions {
+    type PushParams = ();
+    type PullParams = ();
+}
+
+mock! {
+    LightActions {}
+    impl ILightActions for LightActions {
+        fn switched_on(&mut self, event: <MockLightActions as ILightEventParams>::ToggleParams);
+        fn switched_off(&mut self, event: <MockLightActions as ILightEventParams>::ToggleParams);
+    }
+}
+
+impl ILightEventParams for MockLightActions {
+    type ToggleParams = ();
+}
+
+mock! {
+    BellActions {}
+    impl IBellActions for BellActions {
+        fn start_ringing(&mut self, event: <MockBellActions as IBellEventParams>::PressParams);
+        fn stop_ringing(&mut self, event: <MockBellActions as IBellEventParams>::ReleaseParams);
+    }
+}
+
+impl IBellEventParams for MockBellActions {
+    type PressParams = ();
+    type ReleaseParams = ();
+}
+
+#[test]
+fn plantuml_fences_are_generated() {
+    let mut door_actions = MockDoorActions::new();
+    door_actions.expect_door_opened().returning(|_| ()).once();
+    let mut light_actions = MockLightActions::new();
+    light_actions.expect_switched_on().returning(|_| ()).once();
+
+    let mut door = door::start(door_actions);
+    let mut light = light::start(light_actions);
+    let _ = door.push(());
+    let _ = light.toggle(());
+}
+
+#[test]
+fn selected_mermaid_fence_is_generated() {
+    let mut actions = MockBellActions::new();
+    actions.expect_start_ringing().returning(|_| ()).once();
+    actions.expect_stop_ringing().returning(|_| ()).once();
+
+    let mut fsm = bell::start(actions);
+    let _ = fsm.press(());
+    let _ = fsm.release(());
+}
@@ -0,0 +1,59 @@
+/// Test FSMs generated from Mermaid state diagrams.
+///
+/// Covers:
+/// - Format detection by the `.mmd` extension and the `format` option
+/// - Composite states, choices and state descriptions
+use mockall::mock;
+use phyto_fsm::generate_fsm;
+generate_fsm!("test/mermaid/door.mmd");
+
+use mermaid_door::{IMermaidDoorActions, IMermaidDoorEventParams};
+
+mock! {
+    DoorActions {}
+    impl IMermaidDoorActions for DoorActions {
+        fn locking(&mut self, event: <MockDoorActions as IMermaidDoorEventParams>::LockParams);
+        fn unlocking(&mut self);
+        fn ventilate(&mut self);
+        fn is_unlocked(&self, event: &<MockDoorActions as IMermaidDoorEventParams>::PushParams) -> bool;
+        fn code_valid(&self) -> bool;
+        fn code_invalid(&self) -> bool;
+    }
+}
+
+impl IMermaidDoorEventParams for MockDoorActions {
+    type LockParams = ();
+    type EnterCodeParams = u32;
+    type PushParams = ();
+    type PullParams = ();
+}
+
+#[test]
+fn choice_is_left_by_its_guarded_transitions() {
+    let mut actions = MockDoorActions::new();
+    actions.expect_locking().returning(|_| ()).once();
+    actions.expect_code_valid().returning(|| false).once();
+    actions.expect_code_invalid().returning(|| true).once();
+    actions.expect_is_unlocked().returning(|_| false).once();
+    actions.expect_ventilate().never();
+
+    let mut fsm = mermaid_door::start(actions);
+    let _ = fsm.lock(());
+    let _ = fsm.enter_code(1234);
+    let _ = fsm.push(());
+}
+
+#[test]
+fn enter_action_of_aliased_state() {
+    let mut actions = MockDoorActions::new();
+    actions.expect_is_unlocked().returning(|_| true).once();
+    actions.expect_ventilate().returning(|| ()).once();
+
+    let mut fsm = mermaid_door::start(actions);
+    let _ = fsm.push(());
+}
+
+// The format can also be given explicitly, e.g. for Mermaid files with other extensions
+mod explicit_format {
+    phyto_fsm::generate_fsm!(file_path = "test/mermaid/door.mmd", format = "mermaid");
+}
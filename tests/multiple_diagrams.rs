@@ -0,0 +1,84 @@
+/// Test files containing several diagrams.
+///
+/// Covers:
+/// - Generating a module for every diagram with `generate_fsms!`
+/// - Selecting a single diagram with the `diagram` option
+use mockall::mock;
+use phyto_fsm::generate_fsms;
+generate_fsms!("test/misc/multiple_diagrams.puml");
+
+use door::{IDoorActions, IDoorEventParams};
+use light::{ILightActions, ILightEventParams};
+
+mock! {
+    DoorActions {}
+    impl IDoorActions for DoorActions {
+        fn door_opened(&mut self, event: <MockDoorActions as IDoorEventParams>::PushParams);
+        fn door_closed(&mut self, event: <MockDoorActions as IDoorEventParams>::PullParams);
+    }
+}
+
+impl IDoorEventParams for MockDoorActions {
+    type PushParams = ();
+    type PullParams = ();
+}
+
+mock! {
+    LightActions {}
+    impl ILightActions for LightActions {
+        fn switched_on(&mut self, event: <MockLightActions as ILightEventParams>::ToggleParams);
+        fn switched_off(&mut self, event: <MockLightActions as ILightEventParams>::ToggleParams);
+    }
+}
+
+impl ILightEventParams for MockLightActions {
+    type ToggleParams = ();
+}
+
+#[test]
+fn all_diagrams_are_generated() {
+    let mut door_actions = MockDoorActions::new();
+    door_actions.expect_door_opened().returning(|_| ()).once();
+    door_actions.expect_door_closed().returning(|_| ()).once();
+    let mut light_actions = MockLightActions::new();
+    light_actions.expect_switched_on().returning(|_| ()).once();
+
+    let mut door = door::start(door_actions);
+    let mut light = light::start(light_actions);
+    let _ = door.push(());
+    let _ = door.pull(());
+    let _ = light.toggle(());
+}
+
+mod selected {
+    use mockall::mock;
+    phyto_fsm::generate_fsm!(
+        file_path = "test/misc/multiple_diagrams.puml",
+        diagram = "Light"
+    );
+
+    use light::{ILightActions, ILightEventParams};
+
+    mock! {
+        LightActions {}
+        impl ILightActions for LightActions {
+            fn switched_on(&mut self, event: <MockLightActions as ILightEventParams>::ToggleParams);
+            fn switched_off(&mut self, event: <MockLightActions as ILightEventParams>::ToggleParams);
+        }
+    }
+
+    impl ILightEventParams for MockLightActions {
+        type ToggleParams = ();
+    }
+
+    #[test]
+    fn selected_diagram_is_generated() {
+        let mut actions = MockLightActions::new();
+        actions.expect_switched_on().returning(|_| ()).once();
+        actions.expect_switched_off().returning(|_| ()).once();
+
+        let mut fsm = light::start(actions);
+        let _ = fsm.toggle(());
+        let _ = fsm.toggle(());
+    }
+}
@@ -0,0 +1,48 @@
+/// Test that names which are no plain Rust identifiers are mapped to valid ones.
+///
+/// Covers:
+/// - Unicode, underscores and dashes in state, event and action names
+/// - Keywords escaped as raw identifiers (`type`, `match`, `loop`) or suffixed (`self`)
+use phyto_fsm::generate_fsm;
+generate_fsm!("test/misc/naming.puml");
+
+use naming::{INamingActions, INamingEventParams, NoEventData};
+
+#[derive(Default)]
+struct Recorder {
+    calls: Vec<&'static str>,
+}
+
+impl INamingEventParams for Recorder {
+    type GoToCoolParams = NoEventData;
+    type MatchParams = bool;
+}
+
+impl INamingActions for Recorder {
+    fn r#type(&mut self, _: NoEventData) {
+        self.calls.push("type");
+    }
+
+    fn heat_up(&mut self, _: bool) {
+        self.calls.push("heat_up");
+    }
+
+    fn self_(&mut self) {
+        self.calls.push("self");
+    }
+
+    fn r#loop(&self, matched: &bool) -> bool {
+        *matched
+    }
+}
+
+#[test]
+fn mapped_names_are_callable() {
+    let mut fsm = naming::start(Recorder::default());
+
+    let _ = fsm.go_to_cool(());
+    let _ = fsm.r#match(false);
+    let _ = fsm.r#match(true);
+
+    assert_eq!(fsm.actions().calls, ["type", "self", "heat_up"]);
+}
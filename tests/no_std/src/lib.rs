@@ -0,0 +1,67 @@
+//! Compiles the generated code in a `no_std` crate, as part of `cargo build --workspace`.
+#![no_std]
+
+use phyto_fsm::generate_fsm;
+
+generate_fsm!(
+    source = r#"
+    @startuml Door
+    [*] --> Closed
+    Closed : Lock /defer
+    Closed --> Open : Push [IsUnlocked] / Swing
+    Open --> Closed : Release
+    Open --> Locked : Lock
+    @enduml
+    "#,
+    deferred_capacity = 2,
+    log_level = "info"
+);
+
+/// The deferred events are queued inline
+pub fn push_and_lock() -> door::DoorStateId {
+    struct Actions;
+
+    impl door::IDoorEventParams for Actions {
+        type PushParams = ();
+        type ReleaseParams = ();
+        type LockParams = ();
+    }
+
+    impl door::IDoorActions for Actions {
+        fn swing(&mut self, _: ()) {}
+        fn is_unlocked(&self, _: &()) -> bool {
+            true
+        }
+    }
+
+    let mut fsm = door::start(Actions);
+    let _ = fsm.lock(());
+    let _ = fsm.push(());
+    fsm.current_state()
+}
+
+generate_fsm!(
+    file_path = "workspace:src/test/four_seasons/four_seasons.puml",
+    deferred_capacity = 4,
+    log_level = "debug"
+);
+
+/// With `alloc`, the deferred events are queued on the heap and actions may be async
+mod with_alloc {
+    phyto_fsm::generate_fsm!(
+        file_path = "workspace:src/test/deferred_events/deferred.puml",
+        fallible_actions = true,
+        async_actions = true
+    );
+}
+
+/// Logged with `defmt`, which formats the state ids and event kinds itself
+mod with_defmt {
+    phyto_fsm::generate_fsm!(
+        file_path = "workspace:src/test/deferred_events/deferred.puml",
+        deferred_capacity = 2,
+        log_level = "info",
+        logger = "defmt",
+        log_format = "[{fsm}] {message}"
+    );
+}
@@ -0,0 +1,107 @@
+/// An observer attached to the FSM, reporting its steps.
+///
+/// Covers:
+/// - Events, guards, effects, exits and enters in the order of dispatch
+/// - Deferred events and direct transitions
+/// - The FSM of `start()`, which has no observer
+use phyto_fsm::generate_fsm;
+generate_fsm!(
+    source = r#"
+    @startuml Valve
+    [*] --> Closed
+    Closed : Flush /defer
+    Closed --> Opening : Open [HasPressure] / StartMotor
+    Opening --> Opened : / StopMotor
+    state Opened {
+      [*] --> Flowing
+      Flowing : Flush / Rinse
+    }
+    @enduml
+    "#
+);
+
+use valve::{IValveActions, IValveEventParams, IValveObserver, ValveEventKind, ValveStateId};
+
+struct Actions;
+
+impl IValveEventParams for Actions {
+    type FlushParams = ();
+    type OpenParams = bool;
+}
+
+impl IValveActions for Actions {
+    fn start_motor(&mut self, _: bool) {}
+    fn stop_motor(&mut self) {}
+    fn rinse(&mut self, _: ()) {}
+    fn has_pressure(&self, pressure: &bool) -> bool {
+        *pressure
+    }
+}
+
+#[derive(Default)]
+struct Recorder(Vec<String>);
+
+impl IValveObserver for Recorder {
+    fn on_event(&mut self, state: ValveStateId, event: ValveEventKind) {
+        self.0.push(format!("event {event} in {state}"));
+    }
+    fn on_guard(&mut self, guard: &'static str, result: bool) {
+        self.0.push(format!("guard {guard} {result}"));
+    }
+    fn on_exit(&mut self, state: ValveStateId) {
+        self.0.push(format!("exit {state}"));
+    }
+    fn on_effect(&mut self, action: &'static str) {
+        self.0.push(format!("effect {action}"));
+    }
+    fn on_enter(&mut self, state: ValveStateId) {
+        self.0.push(format!("enter {state}"));
+    }
+    fn on_defer(&mut self, state: ValveStateId, event: ValveEventKind) {
+        self.0.push(format!("defer {event} in {state}"));
+    }
+    fn on_direct_transition(&mut self, from: ValveStateId, to: ValveStateId) {
+        self.0.push(format!("direct {from} -> {to}"));
+    }
+}
+
+#[test]
+fn observer_reports_the_steps_in_order() {
+    let mut fsm = valve::start_with_observer(Actions, Recorder::default());
+    assert_eq!(fsm.observer().0, vec!["enter Closed"]);
+    fsm.observer_mut().0.clear();
+
+    let _ = fsm.open(false);
+    let _ = fsm.flush(());
+    let _ = fsm.open(true);
+    assert_eq!(fsm.current_state(), ValveStateId::OpenedFlowing);
+
+    assert_eq!(
+        fsm.observer().0,
+        vec![
+            "event Open in Closed",
+            "guard HasPressure false",
+            "event Flush in Closed",
+            "defer Flush in Closed",
+            "event Open in Closed",
+            "guard HasPressure true",
+            "effect StartMotor",
+            "exit Closed",
+            "enter Opening",
+            "effect StopMotor",
+            "direct Opening -> Opened",
+            "exit Opening",
+            "enter Opened",
+            "enter Opened::Flowing",
+            "event Flush in Opened::Flowing",
+            "effect Rinse",
+        ]
+    );
+}
+
+#[test]
+fn fsm_without_observer_observes_nothing() {
+    let mut fsm: valve::Valve<Actions> = valve::start(Actions);
+    let _ = fsm.open(true);
+    assert_eq!(fsm.current_state(), ValveStateId::OpenedFlowing);
+}
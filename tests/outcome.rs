@@ -0,0 +1,71 @@
+/// The outcome returned when dispatching an event.
+use phyto_fsm::generate_fsm;
+generate_fsm!(
+    r#"
+    @startuml Outcomes
+    [*] --> Idle
+    Idle --> Busy : Start [CanStart]
+    Idle : Poll / Polled
+    Idle : Finish /defer
+    Busy --> Idle : Halt
+    Busy --> Done : Finish
+    Done --> Idle : / Reset
+    @enduml
+    "#
+);
+
+use outcomes::{
+    IOutcomesActions, IOutcomesEventParams, OutcomesEvent, OutcomesOutcome, OutcomesStateId,
+};
+
+struct Actions;
+
+impl IOutcomesEventParams for Actions {
+    type StartParams = bool;
+    type PollParams = ();
+    type FinishParams = ();
+    type HaltParams = ();
+}
+
+impl IOutcomesActions for Actions {
+    fn polled(&mut self, _: ()) {}
+    fn reset(&mut self) {}
+    fn can_start(&self, allowed: &bool) -> bool {
+        *allowed
+    }
+}
+
+#[test]
+fn outcome_of_each_dispatch() {
+    let mut fsm = outcomes::start(Actions);
+
+    assert_eq!(fsm.halt(()), OutcomesOutcome::Ignored);
+    assert_eq!(fsm.start(false), OutcomesOutcome::GuardRejected);
+    assert_eq!(fsm.poll(()), OutcomesOutcome::Internal);
+    assert_eq!(fsm.finish(()), OutcomesOutcome::Deferred);
+    assert_eq!(fsm.current_state(), OutcomesStateId::Idle);
+
+    assert_eq!(
+        fsm.trigger(OutcomesEvent::Start(true)),
+        OutcomesOutcome::Transitioned {
+            from: OutcomesStateId::Idle,
+            to: OutcomesStateId::Busy,
+        }
+    );
+    // The deferred event was processed afterwards
+    assert_eq!(fsm.current_state(), OutcomesStateId::Idle);
+}
+
+#[test]
+fn outcome_includes_direct_transitions() {
+    let mut fsm = outcomes::start(Actions);
+    let _ = fsm.start(true);
+
+    assert_eq!(
+        fsm.finish(()),
+        OutcomesOutcome::Transitioned {
+            from: OutcomesStateId::Busy,
+            to: OutcomesStateId::Idle,
+        }
+    );
+}
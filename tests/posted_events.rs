@@ -0,0 +1,121 @@
+/// Events posted by the actions, processed with run-to-completion semantics.
+///
+/// Covers:
+/// - Posted events processed after the transition completed, in the order of posting
+/// - Deferred events processed again before the posted events
+/// - Posting bounded by the capacity, also for actions posting endlessly
+use phyto_fsm::generate_fsm;
+generate_fsm!(
+    source = r#"
+    @startuml Conveyor
+    [*] --> Stopped
+    Stopped : Load /defer
+    Stopped --> Running : Start / SpinUp
+    Running : entry / Ready
+    Running : Load / Move
+    Running --> Stopped : Halt
+    @enduml
+    "#,
+    posted_capacity = 3
+);
+
+use conveyor::{
+    ConveyorEvent, ConveyorOutcome, ConveyorPostedEvents, ConveyorStateId, IConveyorActions,
+    IConveyorEventParams,
+};
+
+#[derive(Default)]
+struct Actions {
+    log: Vec<String>,
+    /// Posted by `SpinUp`
+    loads: Vec<u32>,
+    /// Whether `Move` posts the next load
+    reload: bool,
+}
+
+impl IConveyorEventParams for Actions {
+    type LoadParams = u32;
+    type StartParams = ();
+    type HaltParams = ();
+}
+
+impl IConveyorActions for Actions {
+    fn spin_up(&mut self, _: (), posted: &mut ConveyorPostedEvents<Self>) {
+        self.log.push("spin_up".to_string());
+        for load in self.loads.drain(..) {
+            posted.post(ConveyorEvent::Load(load)).unwrap();
+        }
+    }
+
+    fn ready(&mut self, _: &mut ConveyorPostedEvents<Self>) {
+        self.log.push("ready".to_string());
+    }
+
+    fn r#move(&mut self, load: u32, posted: &mut ConveyorPostedEvents<Self>) {
+        self.log.push(format!("move {load}"));
+        if self.reload && posted.post(ConveyorEvent::Load(load + 1)).is_err() {
+            self.log.push("rejected".to_string());
+        }
+    }
+}
+
+#[test]
+fn posted_events_follow_the_transition() {
+    let actions = Actions {
+        loads: vec![1, 2],
+        ..Default::default()
+    };
+    let mut fsm = conveyor::start(actions);
+
+    let outcome = fsm.start(());
+    assert_eq!(
+        outcome,
+        ConveyorOutcome::Transitioned {
+            from: ConveyorStateId::Stopped,
+            to: ConveyorStateId::Running
+        }
+    );
+    assert_eq!(
+        fsm.actions().log,
+        vec!["spin_up", "ready", "move 1", "move 2"]
+    );
+}
+
+#[test]
+fn deferred_events_precede_the_posted_events() {
+    let actions = Actions {
+        loads: vec![1],
+        ..Default::default()
+    };
+    let mut fsm = conveyor::start(actions);
+
+    assert_eq!(fsm.load(0), ConveyorOutcome::Deferred);
+    let _ = fsm.start(());
+    assert_eq!(
+        fsm.actions().log,
+        vec!["spin_up", "ready", "move 0", "move 1"]
+    );
+}
+
+#[test]
+fn posting_is_bounded_by_the_capacity() {
+    let actions = Actions {
+        reload: true,
+        ..Default::default()
+    };
+    let mut fsm = conveyor::start(actions);
+    let _ = fsm.start(());
+    fsm.actions_mut().log.clear();
+
+    let _ = fsm.load(0);
+    assert_eq!(
+        fsm.actions().log,
+        vec!["move 0", "move 1", "move 2", "move 3", "rejected"]
+    );
+
+    // Events are posted again for the next event
+    fsm.actions_mut().log.clear();
+    let _ = fsm.load(10);
+    assert_eq!(fsm.actions().log.len(), 5);
+    assert_eq!(fsm.current_state(), ConveyorStateId::Running);
+}
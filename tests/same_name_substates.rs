@@ -0,0 +1,47 @@
+use phyto_fsm::generate_fsm;
+generate_fsm!(
+    file_path = "../src/test/composite_states/same_name_substates.puml",
+    log_level = "debug"
+);
+
+use mockall::mock;
+use same_name_substates::{ISameNameSubstatesActions, ISameNameSubstatesEventParams};
+
+mock! {
+    SameNameSubstatesActions {}
+    impl ISameNameSubstatesActions for SameNameSubstatesActions {
+        fn back_to_inner_a(&mut self, event: <MockSameNameSubstatesActions as ISameNameSubstatesEventParams>::BackToAParams);
+        fn enter_inner_a(&mut self);
+    }
+}
+
+impl ISameNameSubstatesEventParams for MockSameNameSubstatesActions {
+    type ToOtherParams = ();
+    type ToBParams = ();
+    type BackToAParams = ();
+}
+
+#[test]
+fn qualified_transition_targets_substate_of_named_parent() {
+    let mut actions = MockSameNameSubstatesActions::new();
+    // ParentA::Inner is entered on start and after returning from ParentB::Other
+    actions.expect_enter_inner_a().returning(|| ()).times(2);
+    actions.expect_back_to_inner_a().returning(|_| ()).times(1);
+
+    let mut fsm = same_name_substates::start(actions);
+    let _ = fsm.to_b(());
+    let _ = fsm.to_other(());
+    let _ = fsm.back_to_a(());
+}
+
+#[test]
+fn qualified_transition_ignores_substate_of_other_parent() {
+    let mut actions = MockSameNameSubstatesActions::new();
+    actions.expect_enter_inner_a().returning(|| ()).times(1);
+    // ParentA::Other has no transition for backToA
+    actions.expect_back_to_inner_a().never();
+
+    let mut fsm = same_name_substates::start(actions);
+    let _ = fsm.to_other(());
+    let _ = fsm.back_to_a(());
+}
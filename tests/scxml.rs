@@ -0,0 +1,83 @@
+/// Test FSMs generated from SCXML documents.
+///
+/// Covers:
+/// - Format detection by the `.scxml` extension
+/// - Initial states, nested states, guards, internal transitions and enter/exit actions
+use mockall::{Sequence, mock};
+use phyto_fsm::generate_fsm;
+generate_fsm!("test/scxml/traffic_light.scxml");
+
+use traffic_light::{ITrafficLightActions, ITrafficLightEventParams};
+
+mock! {
+    LightActions {}
+    impl ITrafficLightActions for LightActions {
+        fn report_fault(&mut self, event: <MockLightActions as ITrafficLightEventParams>::FaultParams);
+        fn register_request(&mut self, event: <MockLightActions as ITrafficLightEventParams>::RequestParams);
+        fn pedestrian_waiting(&self, event: &<MockLightActions as ITrafficLightEventParams>::TickParams) -> bool;
+        fn stop_traffic(&mut self);
+        fn warn_traffic(&mut self);
+    }
+}
+
+impl ITrafficLightEventParams for MockLightActions {
+    type FaultParams = ();
+    type RequestParams = ();
+    type TickParams = ();
+    type RepairParams = ();
+}
+
+#[test]
+fn initial_states_are_entered() {
+    let mut actions = MockLightActions::new();
+    actions.expect_stop_traffic().returning(|| ()).once();
+
+    let _fsm = traffic_light::start(actions);
+}
+
+#[test]
+fn cycles_through_nested_states() {
+    let mut actions = MockLightActions::new();
+    let mut seq = Sequence::new();
+    actions
+        .expect_stop_traffic()
+        .returning(|| ())
+        .once()
+        .in_sequence(&mut seq);
+    actions
+        .expect_register_request()
+        .returning(|_| ())
+        .once()
+        .in_sequence(&mut seq);
+    actions
+        .expect_pedestrian_waiting()
+        .returning(|_| true)
+        .once()
+        .in_sequence(&mut seq);
+    actions
+        .expect_warn_traffic()
+        .returning(|| ())
+        .once()
+        .in_sequence(&mut seq);
+    actions
+        .expect_stop_traffic()
+        .returning(|| ())
+        .once()
+        .in_sequence(&mut seq);
+
+    let mut fsm = traffic_light::start(actions);
+    let _ = fsm.tick(());
+    let _ = fsm.request(());
+    let _ = fsm.tick(());
+}
+
+#[test]
+fn fault_leaves_composite_state() {
+    let mut actions = MockLightActions::new();
+    actions.expect_stop_traffic().returning(|| ()).times(2);
+    actions.expect_report_fault().returning(|_| ()).once();
+
+    let mut fsm = traffic_light::start(actions);
+    let _ = fsm.fault(());
+    let _ = fsm.repair(());
+}
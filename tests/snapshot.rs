@@ -0,0 +1,198 @@
+/// Snapshots of the FSM, serialized with serde to resume it later.
+///
+/// Covers:
+/// - Restoring the state and the deferred events without running enter actions
+/// - Snapshots of another version of the diagram are rejected, also when only its error
+///   transitions differ
+/// - Snapshots of the FSM before it was started are rejected
+/// - Restoring with an observer, which reports the steps after restoring
+use phyto_fsm::generate_fsm;
+generate_fsm!(
+    source = r#"
+    @startuml Heater
+    [*] --> Off
+    Off --> On : PowerOn
+    state On {
+      On : entry / Ignite
+      [*] --> Heating
+      Heating : SetTarget /defer
+      Heating --> Idle : TargetReached
+      Idle : entry / Rest
+      Idle --> Heating : SetTarget / ApplyTarget
+    }
+    @enduml
+    "#,
+    serde = true
+);
+
+// The same diagram, which can also be switched off
+generate_fsm!(
+    source = r#"
+    @startuml HeaterV2
+    [*] --> Off
+    Off --> On : PowerOn
+    On --> Off : PowerOff
+    state On {
+      On : entry / Ignite
+      [*] --> Heating
+      Heating : SetTarget /defer
+      Heating --> Idle : TargetReached
+      Idle : entry / Rest
+      Idle --> Heating : SetTarget / ApplyTarget
+    }
+    @enduml
+    "#,
+    serde = true
+);
+
+// Two versions of a diagram which only differ in where failures are routed
+mod valve_v1 {
+    pub use valve::DIAGRAM_HASH;
+    phyto_fsm::generate_fsm!(
+        source = r#"
+        @startuml Valve
+        [*] --> Closed
+        Closed --> Open : Turn
+        Open --> Jammed : Block
+        Open --> Closed : error
+        @enduml
+        "#,
+        fallible_actions = true,
+        serde = true
+    );
+}
+
+mod valve_v2 {
+    pub use valve::DIAGRAM_HASH;
+    phyto_fsm::generate_fsm!(
+        source = r#"
+        @startuml Valve
+        [*] --> Closed
+        Closed --> Open : Turn
+        Open --> Jammed : Block
+        Open --> Jammed : error
+        @enduml
+        "#,
+        fallible_actions = true,
+        serde = true
+    );
+}
+
+use heater::{
+    HeaterRestoreError, HeaterSnapshot, HeaterStateId, IHeaterActions, IHeaterEventParams,
+    IHeaterObserver,
+};
+use heater_v2::{HeaterV2RestoreError, IHeaterV2Actions, IHeaterV2EventParams};
+
+#[derive(Default)]
+struct Actions {
+    calls: Vec<String>,
+}
+
+impl IHeaterEventParams for Actions {
+    type PowerOnParams = ();
+    type SetTargetParams = u32;
+    type TargetReachedParams = ();
+}
+
+impl IHeaterActions for Actions {
+    fn apply_target(&mut self, target: u32) {
+        self.calls.push(format!("apply_target {target}"));
+    }
+    fn ignite(&mut self) {
+        self.calls.push("ignite".to_string());
+    }
+    fn rest(&mut self) {
+        self.calls.push("rest".to_string());
+    }
+}
+
+impl IHeaterV2EventParams for Actions {
+    type PowerOnParams = ();
+    type PowerOffParams = ();
+    type SetTargetParams = u32;
+    type TargetReachedParams = ();
+}
+
+impl IHeaterV2Actions for Actions {
+    fn apply_target(&mut self, _: u32) {}
+    fn ignite(&mut self) {}
+    fn rest(&mut self) {}
+}
+
+fn heating_with_deferred_target() -> String {
+    let mut fsm = heater::start(Actions::default());
+    let _ = fsm.power_on(());
+    let _ = fsm.set_target(21);
+    assert_eq!(fsm.current_state(), HeaterStateId::OnHeating);
+    serde_json::to_string(&fsm.snapshot()).unwrap()
+}
+
+#[test]
+fn restore_resumes_with_the_deferred_events() {
+    let json = heating_with_deferred_target();
+
+    let snapshot: HeaterSnapshot<Actions> = serde_json::from_str(&json).unwrap();
+    assert_eq!(snapshot.state(), HeaterStateId::OnHeating);
+    let mut fsm = heater::restore(snapshot, Actions::default()).unwrap();
+    assert_eq!(fsm.current_state(), HeaterStateId::OnHeating);
+    assert!(fsm.actions().calls.is_empty());
+
+    let _ = fsm.target_reached(());
+    assert_eq!(fsm.current_state(), HeaterStateId::OnHeating);
+    assert_eq!(fsm.actions().calls, vec!["rest", "apply_target 21"]);
+}
+
+#[derive(Default)]
+struct Recorder(Vec<String>);
+
+impl IHeaterObserver for Recorder {
+    fn on_enter(&mut self, state: HeaterStateId) {
+        self.0.push(format!("enter {state}"));
+    }
+}
+
+#[test]
+fn restore_with_observer_reports_the_steps_after_restoring() {
+    let json = heating_with_deferred_target();
+
+    let snapshot: HeaterSnapshot<Actions> = serde_json::from_str(&json).unwrap();
+    let mut fsm =
+        heater::restore_with_observer(snapshot, Actions::default(), Recorder::default()).unwrap();
+    assert!(fsm.observer().0.is_empty());
+
+    let _ = fsm.target_reached(());
+    assert_eq!(
+        fsm.observer().0,
+        vec!["enter On::Idle", "enter On::Heating"]
+    );
+}
+
+#[test]
+fn restore_rejects_another_diagram_version() {
+    let json = heating_with_deferred_target();
+
+    let snapshot: heater_v2::HeaterV2Snapshot<Actions> = serde_json::from_str(&json).unwrap();
+    let result = heater_v2::restore(snapshot, Actions::default());
+    assert_eq!(
+        result.err(),
+        Some(HeaterV2RestoreError::DiagramMismatch {
+            expected: heater_v2::DIAGRAM_HASH,
+            found: heater::DIAGRAM_HASH,
+        })
+    );
+}
+
+#[test]
+fn diagram_hash_covers_the_error_transitions() {
+    assert_ne!(valve_v1::DIAGRAM_HASH, valve_v2::DIAGRAM_HASH);
+}
+
+#[test]
+fn restore_rejects_snapshot_before_start() {
+    let json = heating_with_deferred_target().replace("OnHeating", "_HeaterInitialState_");
+
+    let snapshot: HeaterSnapshot<Actions> = serde_json::from_str(&json).unwrap();
+    let result = heater::restore(snapshot, Actions::default());
+    assert_eq!(result.err(), Some(HeaterRestoreError::NotStarted));
+}
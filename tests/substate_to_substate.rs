@@ -28,7 +28,7 @@ fn should_transition_from_substate_to_substate_across_parents() {
     actions.expect_action_in_aa().returning(|_| ()).times(1);
 
     let mut fsm = substate_to_substate::start(actions);
-    fsm.to_ba(());
+    let _ = fsm.to_ba(());
 }
 
 #[test]
@@ -39,6 +39,6 @@ fn should_transition_within_substate() {
     actions.expect_action_in_ba().returning(|_| ()).times(1);
 
     let mut fsm = substate_to_substate::start(actions);
-    fsm.to_ba(());
-    fsm.to_bb(());
+    let _ = fsm.to_ba(());
+    let _ = fsm.to_bb(());
 }
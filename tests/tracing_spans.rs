@@ -0,0 +1,179 @@
+/// Spans and events of the `tracing` crate.
+///
+/// Covers:
+/// - A span per dispatched event, recording the source and final state
+/// - Events for guards, exits, effects, enters and deferrals within the span
+/// - Deferred events processed again in child spans
+use std::sync::{Arc, Mutex};
+
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+use phyto_fsm::generate_fsm;
+generate_fsm!(
+    source = r#"
+    @startuml Gate
+    [*] --> Closed
+    Closed : Pass /defer
+    Closed --> Opened : Open [HasTicket] / Unlock
+    Opened --> Opened : Pass / Count
+    @enduml
+    "#,
+    tracing = "debug"
+);
+
+use gate::{IGateActions, IGateEventParams};
+
+struct Actions;
+
+impl IGateEventParams for Actions {
+    type PassParams = ();
+    type OpenParams = bool;
+}
+
+impl IGateActions for Actions {
+    fn unlock(&mut self, _: bool) {}
+    fn count(&mut self, _: ()) {}
+    fn has_ticket(&self, ticket: &bool) -> bool {
+        *ticket
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct SpanRecord {
+    name: &'static str,
+    parent: Option<usize>,
+    fields: Vec<String>,
+}
+
+/// Records the spans and the events, which are named after their innermost span
+#[derive(Default)]
+struct Recorder {
+    spans: Mutex<Vec<SpanRecord>>,
+    stack: Mutex<Vec<usize>>,
+    events: Mutex<Vec<String>>,
+}
+
+#[derive(Default)]
+struct Fields(Vec<String>);
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0.push(format!("{}={:?}", field.name(), value));
+    }
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.push(format!("{}={}", field.name(), value));
+    }
+}
+
+struct Shared(Arc<Recorder>);
+
+impl Subscriber for Shared {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, attrs: &Attributes<'_>) -> Id {
+        let mut fields = Fields::default();
+        attrs.record(&mut fields);
+        let mut spans = self.0.spans.lock().unwrap();
+        spans.push(SpanRecord {
+            name: attrs.metadata().name(),
+            parent: self.0.stack.lock().unwrap().last().copied(),
+            fields: fields.0,
+        });
+        Id::from_u64(spans.len() as u64)
+    }
+
+    fn record(&self, span: &Id, values: &Record<'_>) {
+        let mut fields = Fields::default();
+        values.record(&mut fields);
+        let index = span.into_u64() as usize - 1;
+        self.0.spans.lock().unwrap()[index].fields.extend(fields.0);
+    }
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        let span = self.0.stack.lock().unwrap().last().copied();
+        self.0
+            .events
+            .lock()
+            .unwrap()
+            .push(format!("{span:?}: {}", fields.0.join(" ")));
+    }
+
+    fn enter(&self, span: &Id) {
+        let index = span.into_u64() as usize - 1;
+        self.0.stack.lock().unwrap().push(index);
+    }
+
+    fn exit(&self, _: &Id) {
+        self.0.stack.lock().unwrap().pop();
+    }
+}
+
+#[test]
+fn dispatches_are_traced_in_spans() {
+    let recorder = Arc::new(Recorder::default());
+    tracing::subscriber::with_default(Shared(recorder.clone()), || {
+        let mut fsm = gate::start(Actions);
+        let _ = fsm.pass(());
+        let _ = fsm.open(true);
+    });
+
+    let spans = recorder.spans.lock().unwrap();
+    assert_eq!(
+        *spans,
+        vec![
+            SpanRecord {
+                name: "dispatch",
+                parent: None,
+                fields: vec![
+                    "fsm=Gate".into(),
+                    "event=Pass".into(),
+                    "state=Closed".into(),
+                    "final_state=Closed".into(),
+                ],
+            },
+            SpanRecord {
+                name: "dispatch",
+                parent: None,
+                fields: vec![
+                    "fsm=Gate".into(),
+                    "event=Open".into(),
+                    "state=Closed".into(),
+                    "final_state=Opened".into(),
+                ],
+            },
+            SpanRecord {
+                name: "deferred",
+                parent: Some(1),
+                fields: vec![
+                    "fsm=Gate".into(),
+                    "event=Pass".into(),
+                    "state=Opened".into(),
+                    "final_state=Opened".into(),
+                ],
+            },
+        ]
+    );
+
+    assert_eq!(
+        *recorder.events.lock().unwrap(),
+        vec![
+            "None: message=enter state=Closed",
+            "Some(0): message=defer state=Closed event=Pass",
+            "Some(1): message=guard guard=HasTicket result=true",
+            "Some(1): message=effect action=Unlock",
+            "Some(1): message=exit state=Closed",
+            "Some(1): message=enter state=Opened",
+            "Some(2): message=effect action=Count",
+            "Some(2): message=exit state=Opened",
+            "Some(2): message=enter state=Opened",
+        ]
+    );
+}
@@ -30,8 +30,8 @@ fn self_transition_action_called() {
     actions.expect_action2().returning(|_| ()).times(1);
     let mut fsm = test_fsm::start(actions);
 
-    fsm.self_transition(());
-    fsm.go_to_b(());
+    let _ = fsm.self_transition(());
+    let _ = fsm.go_to_b(());
 }
 
 #[test]
@@ -41,8 +41,8 @@ fn final_state() {
     actions.expect_action2().returning(|_| ()).times(1);
     let mut fsm = test_fsm::start(actions);
 
-    fsm.go_to_b(());
-    fsm.self_transition(());
+    let _ = fsm.go_to_b(());
+    let _ = fsm.self_transition(());
 }
 
 #[test]
@@ -52,5 +52,5 @@ fn alternative_transition() {
     actions.expect_action2().times(0);
     let mut fsm = test_fsm::start(actions);
 
-    fsm.go_to_b_differently(());
+    let _ = fsm.go_to_b_differently(());
 }
@@ -0,0 +1,29 @@
+#![deny(unused_must_use)]
+
+use phyto_fsm::generate_fsm;
+generate_fsm!(
+    source = r#"
+    @startuml Switch
+    [*] --> Off
+    Off --> On : Toggle
+    On --> Off : Toggle
+    @enduml
+    "#,
+    async_actions = true
+);
+
+use switch::{ISwitchActions, ISwitchEventParams, Switch};
+
+struct Actions;
+
+impl ISwitchEventParams for Actions {
+    type ToggleParams = ();
+}
+
+impl ISwitchActions for Actions {}
+
+async fn toggle(fsm: &mut Switch<Actions>) {
+    fsm.toggle(()).await;
+}
+
+fn main() {}
@@ -0,0 +1,28 @@
+#![deny(unused_must_use)]
+
+use phyto_fsm::generate_fsm;
+generate_fsm!(
+    source = r#"
+    @startuml Switch
+    [*] --> Off
+    Off --> On : Toggle
+    On --> Off : Toggle
+    @enduml
+    "#
+);
+
+use switch::{ISwitchActions, ISwitchEventParams, SwitchEvent};
+
+struct Actions;
+
+impl ISwitchEventParams for Actions {
+    type ToggleParams = ();
+}
+
+impl ISwitchActions for Actions {}
+
+fn main() {
+    let mut fsm = switch::start(Actions);
+    fsm.toggle(());
+    fsm.trigger(SwitchEvent::Toggle(()));
+}
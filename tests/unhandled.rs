@@ -0,0 +1,91 @@
+/// The `on_unhandled` hook of the actions trait.
+///
+/// Covers:
+/// - Events without a matching transition
+/// - Events whose guards are all false
+/// - Deferred events which are discarded
+/// - Deferred events still pending when the FSM is stopped or gives the actions back
+use phyto_fsm::generate_fsm;
+generate_fsm!(
+    r#"
+    @startuml Unhandled
+    [*] --> Idle
+    Idle : Finish /defer
+    Idle --> Busy : Start [CanStart]
+    Busy --> Idle : Halt
+    Busy --> Done : Complete
+    Done --> Idle : Finish
+    @enduml
+    "#
+);
+
+use mockall::{Sequence, mock, predicate::eq};
+use unhandled::{IUnhandledActions, IUnhandledEventParams, UnhandledEventKind, UnhandledStateId};
+
+mock! {
+    UnhandledActions {}
+    impl IUnhandledActions for UnhandledActions {
+        fn can_start(&self, event: &bool) -> bool;
+        fn on_unhandled(&mut self, state: UnhandledStateId, event: UnhandledEventKind);
+    }
+}
+
+impl IUnhandledEventParams for MockUnhandledActions {
+    type StartParams = bool;
+    type FinishParams = ();
+    type HaltParams = ();
+    type CompleteParams = ();
+}
+
+#[test]
+fn unhandled_events_are_reported() {
+    let mut actions = MockUnhandledActions::new();
+    let mut seq = Sequence::new();
+    actions.expect_can_start().returning(|allowed| *allowed);
+    actions
+        .expect_on_unhandled()
+        .with(eq(UnhandledStateId::Idle), eq(UnhandledEventKind::Halt))
+        .times(1)
+        .in_sequence(&mut seq)
+        .returning(|_, _| ());
+    actions
+        .expect_on_unhandled()
+        .with(eq(UnhandledStateId::Idle), eq(UnhandledEventKind::Start))
+        .times(1)
+        .in_sequence(&mut seq)
+        .returning(|_, _| ());
+    actions
+        .expect_on_unhandled()
+        .with(eq(UnhandledStateId::Busy), eq(UnhandledEventKind::Finish))
+        .times(1)
+        .in_sequence(&mut seq)
+        .returning(|_, _| ());
+
+    let mut fsm = unhandled::start(actions);
+    let _ = fsm.halt(());
+    let _ = fsm.start(false);
+    // Deferred, then discarded in Busy
+    let _ = fsm.finish(());
+    let _ = fsm.start(true);
+}
+
+#[test]
+fn pending_deferred_events_are_reported_on_stop() {
+    for into_actions in [false, true] {
+        let mut actions = MockUnhandledActions::new();
+        actions
+            .expect_on_unhandled()
+            .with(eq(UnhandledStateId::Idle), eq(UnhandledEventKind::Finish))
+            .times(2)
+            .returning(|_, _| ());
+
+        let mut fsm = unhandled::start(actions);
+        let _ = fsm.finish(());
+        let _ = fsm.finish(());
+        let mut actions = match into_actions {
+            true => fsm.into_actions(),
+            false => fsm.stop(),
+        };
+        actions.checkpoint();
+    }
+}